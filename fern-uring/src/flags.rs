@@ -0,0 +1,134 @@
+//! Typed views of flag words shared with the kernel.
+
+use std::sync::atomic::Ordering;
+
+use rustix::io_uring::IoringSqFlags;
+
+use crate::sync::atomic::AtomicU32;
+
+/// The flags the kernel sets on the submission queue ring.
+///
+/// These tell the application when it must enter the kernel: to wake the SQ polling thread, to
+/// flush overflowed completions, or to run pending task work.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SqFlags(IoringSqFlags);
+
+impl SqFlags {
+    /// Create `SqFlags` from the raw flag word. Unknown bits are retained.
+    #[must_use]
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(IoringSqFlags::from_bits_retain(bits))
+    }
+
+    /// Read the flags from the flag word shared with the kernel.
+    ///
+    /// This is the only place the flag word should be loaded, so every reader observes it with
+    /// the same ordering.
+    #[must_use]
+    pub fn load(flags: &AtomicU32) -> Self {
+        Self::from_bits(flags.load(Ordering::Acquire))
+    }
+
+    /// Get the raw flag word.
+    #[must_use]
+    pub const fn bits(self) -> u32 {
+        self.0.bits()
+    }
+
+    /// The SQ polling thread has gone idle, and must be woken by entering the kernel with
+    /// `IORING_ENTER_SQ_WAKEUP` before it will process new submissions.
+    #[must_use]
+    pub const fn needs_wakeup(self) -> bool {
+        self.0.contains(IoringSqFlags::NEED_WAKEUP)
+    }
+
+    /// The completion queue has overflowed. Completions are being held by the kernel, and are
+    /// flushed by entering the kernel with `IORING_ENTER_GETEVENTS`.
+    #[must_use]
+    pub const fn cq_overflow(self) -> bool {
+        self.0.contains(IoringSqFlags::CQ_OVERFLOW)
+    }
+
+    /// Task work is pending, and completions may not be visible until the kernel is entered with
+    /// `IORING_ENTER_GETEVENTS`. Only set for rings created with
+    /// [`Params::with_cooperative_taskrun`].
+    ///
+    /// [`Params::with_cooperative_taskrun`]: crate::params::Params::with_cooperative_taskrun
+    #[must_use]
+    pub const fn taskrun_pending(self) -> bool {
+        self.0.contains(IoringSqFlags::TASKRUN)
+    }
+}
+
+impl From<IoringSqFlags> for SqFlags {
+    fn from(flags: IoringSqFlags) -> Self {
+        Self(flags)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rustix::io_uring::IoringSqFlags;
+
+    use super::SqFlags;
+    use crate::sync::atomic::AtomicU32;
+
+    #[test]
+    fn no_flags_are_set_by_default() {
+        let flags = SqFlags::default();
+
+        assert!(!flags.needs_wakeup());
+        assert!(!flags.cq_overflow());
+        assert!(!flags.taskrun_pending());
+    }
+
+    #[test]
+    fn reads_need_wakeup() {
+        let flags = SqFlags::from(IoringSqFlags::NEED_WAKEUP);
+
+        assert!(flags.needs_wakeup());
+        assert!(!flags.cq_overflow());
+        assert!(!flags.taskrun_pending());
+    }
+
+    #[test]
+    fn reads_cq_overflow() {
+        let flags = SqFlags::from(IoringSqFlags::CQ_OVERFLOW);
+
+        assert!(!flags.needs_wakeup());
+        assert!(flags.cq_overflow());
+        assert!(!flags.taskrun_pending());
+    }
+
+    #[test]
+    fn reads_taskrun_pending() {
+        let flags = SqFlags::from(IoringSqFlags::TASKRUN);
+
+        assert!(!flags.needs_wakeup());
+        assert!(!flags.cq_overflow());
+        assert!(flags.taskrun_pending());
+    }
+
+    #[test]
+    fn retains_unknown_bits() {
+        let bits = IoringSqFlags::TASKRUN.bits() | 1 << 31;
+        let flags = SqFlags::from_bits(bits);
+
+        assert!(flags.taskrun_pending());
+        assert_eq!(flags.bits(), bits);
+    }
+
+    #[test]
+    fn loads_flags_from_shared_word() {
+        loom::model(|| {
+            let word =
+                AtomicU32::new((IoringSqFlags::NEED_WAKEUP | IoringSqFlags::CQ_OVERFLOW).bits());
+
+            let flags = SqFlags::load(&word);
+
+            assert!(flags.needs_wakeup());
+            assert!(flags.cq_overflow());
+            assert!(!flags.taskrun_pending());
+        });
+    }
+}
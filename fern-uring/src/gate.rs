@@ -0,0 +1,190 @@
+//! A counting semaphore which bounds outstanding submissions to the depth of the SQ.
+//!
+//! The SQ only limits how many entries are waiting for the kernel to consume them. Once consumed,
+//! an operation is still in flight until its completion is reaped. An [`SqGate`] hands out one
+//! permit per SQ entry, taken when an entry is reserved and returned when its completion is
+//! reaped, so submitters block while the ring is saturated and wake as completions drain.
+
+use std::sync::PoisonError;
+
+use crate::sync::{Condvar, Mutex};
+use crate::{BackoffStrategy, ReservedEntry, RingBufferProducer};
+
+/// Bounds outstanding submissions to the size of the SQ.
+///
+/// Waiting for a permit blocks on a [`Condvar`], which parks the thread on a futex rather than
+/// spinning.
+#[derive(Debug)]
+pub struct SqGate<'ring, T> {
+    producer: RingBufferProducer<'ring, T>,
+    strategy: BackoffStrategy,
+    permits: Mutex<u32>,
+    released: Condvar,
+}
+
+impl<'ring, T> SqGate<'ring, T> {
+    /// Create a new `SqGate` around the SQ `producer`, with one permit per entry.
+    ///
+    /// `strategy` is used to wait for a slot in the SQ once a permit is acquired, in case the
+    /// kernel has not yet consumed the entry which used it last.
+    #[must_use]
+    pub fn new(producer: RingBufferProducer<'ring, T>, strategy: BackoffStrategy) -> Self {
+        // ring sizes are validated to fit in a `u32` when the producer is created
+        let permits = u32::try_from(producer.size()).unwrap_or(u32::MAX);
+
+        Self {
+            producer,
+            strategy,
+            permits: Mutex::new(permits),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Get the producer the gate reserves entries from, such as to commit them.
+    #[must_use]
+    pub fn producer(&self) -> &RingBufferProducer<'ring, T> {
+        &self.producer
+    }
+
+    /// Get the number of permits which can currently be acquired without blocking.
+    #[must_use]
+    pub fn available_permits(&self) -> u32 {
+        *self.permits.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Acquire a permit, blocking until one is released if none are available, then reserve an
+    /// entry in the SQ.
+    ///
+    /// The permit is held until [`Self::release`] is called, typically when the completion of
+    /// the submitted operation is reaped.
+    #[must_use]
+    pub fn acquire(&self) -> ReservedEntry<'ring, T> {
+        let mut permits = self.permits.lock().unwrap_or_else(PoisonError::into_inner);
+        while *permits == 0 {
+            permits = self
+                .released
+                .wait(permits)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *permits -= 1;
+        drop(permits);
+
+        self.producer.reserve_blocking(self.strategy)
+    }
+
+    /// Release a permit, waking a thread blocked in [`Self::acquire`].
+    ///
+    /// Must only be called once for each permit acquired. Releasing more permits than were
+    /// acquired panics in debug builds, as it would let more operations be in flight than the SQ
+    /// can hold.
+    pub fn release(&self) {
+        let mut permits = self.permits.lock().unwrap_or_else(PoisonError::into_inner);
+        debug_assert!(
+            (*permits as usize) < self.producer.size(),
+            "released more permits than were acquired, with all {} already available",
+            *permits
+        );
+        *permits += 1;
+        drop(permits);
+
+        self.released.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use loom::thread::{self, yield_now};
+
+    use crate::sync::Arc;
+    use crate::sync::atomic::{AtomicU32, Ordering};
+    use crate::{BackoffStrategy, RingBufferConsumer, RingBufferProducer};
+
+    use super::SqGate;
+
+    #[test]
+    fn permits_start_at_sq_size() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let producer = RingBufferProducer::new(&entries, &head, &tail, 4 - 1).unwrap();
+            let gate = SqGate::new(producer, BackoffStrategy::default());
+
+            assert_eq!(gate.available_permits(), 4);
+            let entry = gate.acquire();
+            assert_eq!(gate.available_permits(), 3);
+            gate.producer().commit(entry).unwrap();
+
+            gate.release();
+            assert_eq!(gate.available_permits(), 4);
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "released more permits than were acquired")]
+    fn releasing_unacquired_permit_panics_in_debug() {
+        loom::model(|| {
+            let entries = vec![0u32; 2];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let producer = RingBufferProducer::new(&entries, &head, &tail, 2 - 1).unwrap();
+            let gate = SqGate::new(producer, BackoffStrategy::default());
+
+            let entry = gate.acquire();
+            gate.producer().commit(entry).unwrap();
+            gate.release();
+
+            gate.release();
+        });
+    }
+
+    #[test]
+    fn throttles_submitters_to_sq_depth() {
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound = Some(2);
+
+        builder.check(|| {
+            const SUBMITTERS: u32 = 2;
+
+            // submitters share a single `SqGate`, which must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![0u32; 1].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let producer = RingBufferProducer::new(entries, head, tail, 0).unwrap();
+            let gate = Arc::new(SqGate::new(producer, BackoffStrategy::spin_only()));
+            let in_flight = Arc::new(AtomicU32::new(0));
+
+            let submitters: Vec<_> = (0..SUBMITTERS)
+                .map(|_| {
+                    let gate = gate.clone();
+                    let in_flight = in_flight.clone();
+                    thread::spawn(move || {
+                        let entry = gate.acquire();
+                        assert_eq!(in_flight.fetch_add(1, Ordering::AcqRel), 0);
+                        gate.producer().commit(entry).unwrap();
+                    })
+                })
+                .collect();
+
+            // act as the kernel consuming submissions, and the application reaping completions
+            let consumer = RingBufferConsumer::new(entries, head, tail, 0).unwrap();
+            let mut reaped = 0;
+            while reaped < SUBMITTERS {
+                if let Some(entry) = consumer.reserve() {
+                    consumer.commit(entry).unwrap();
+                    in_flight.fetch_sub(1, Ordering::AcqRel);
+                    gate.release();
+                    reaped += 1;
+                } else {
+                    yield_now();
+                }
+            }
+
+            for submitter in submitters {
+                submitter.join().unwrap();
+            }
+            assert_eq!(gate.available_permits(), 1);
+        });
+    }
+}
@@ -0,0 +1,223 @@
+//! Tracks when registered buffers lent to in-flight operations can be reused.
+//!
+//! A registered buffer stays pinned for as long as it is registered, so it can be reused as soon
+//! as the kernel is done with it. For fixed reads and writes, that is when the operation completes,
+//! even if it transferred fewer bytes than requested. Zero-copy sends may still be reading the
+//! buffer after they complete, and only release it with a separate notification CQE, flagged with
+//! `IORING_CQE_F_NOTIF`.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use rustix::io_uring::{IoringCqeFlags, io_uring_cqe};
+
+/// Errors that occur as a result of using [`BufferLease`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LeaseError {
+    /// No registered buffer has the given index.
+    UnknownBuffer,
+    /// The buffer is still lent to an in-flight operation.
+    Loaned,
+    /// Another lent buffer is already tracked under the same `user_data`.
+    UserDataInUse,
+}
+
+impl LeaseError {
+    /// Get a description of the error, without going through the formatting machinery.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnknownBuffer => "No registered buffer has the given index.",
+            Self::Loaned => "The buffer is still lent to an in-flight operation.",
+            Self::UserDataInUse => {
+                "Another lent buffer is already tracked under the same user_data."
+            }
+        }
+    }
+}
+
+impl Display for LeaseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Loan {
+    user_data: u64,
+    zero_copy: bool,
+}
+
+/// Tracks which registered buffers are lent to in-flight operations, and releases each once the
+/// completion that ends its loan is reaped.
+#[derive(Debug)]
+pub struct BufferLease {
+    loans: Vec<Option<Loan>>,
+    by_user_data: HashMap<u64, u16>,
+}
+
+impl BufferLease {
+    /// Create a new `BufferLease` over `buffers` registered buffers, none of which are lent.
+    #[must_use]
+    pub fn new(buffers: u16) -> Self {
+        Self {
+            loans: vec![None; usize::from(buffers)],
+            by_user_data: HashMap::new(),
+        }
+    }
+
+    /// Determines if the buffer at `index` is lent to an in-flight operation.
+    #[must_use]
+    pub fn is_loaned(&self, index: u16) -> bool {
+        self.loans
+            .get(usize::from(index))
+            .is_some_and(Option::is_some)
+    }
+
+    /// Lend the buffer at `index` to the fixed read or write identified by `user_data`.
+    ///
+    /// The buffer becomes reusable once the operation completes.
+    ///
+    /// # Errors
+    /// - if no registered buffer has the given `index`, returns [`LeaseError::UnknownBuffer`].
+    /// - if the buffer is still lent, returns [`LeaseError::Loaned`].
+    /// - if another buffer is lent to `user_data`, returns [`LeaseError::UserDataInUse`].
+    pub fn lend(&mut self, index: u16, user_data: u64) -> Result<(), LeaseError> {
+        self.lend_internal(index, user_data, false)
+    }
+
+    /// Lend the buffer at `index` to the zero-copy send identified by `user_data`.
+    ///
+    /// The buffer only becomes reusable once the notification CQE arrives, or once the send
+    /// completes without `IORING_CQE_F_MORE`, meaning no notification will follow.
+    ///
+    /// # Errors
+    /// - if no registered buffer has the given `index`, returns [`LeaseError::UnknownBuffer`].
+    /// - if the buffer is still lent, returns [`LeaseError::Loaned`].
+    /// - if another buffer is lent to `user_data`, returns [`LeaseError::UserDataInUse`].
+    pub fn lend_zero_copy(&mut self, index: u16, user_data: u64) -> Result<(), LeaseError> {
+        self.lend_internal(index, user_data, true)
+    }
+
+    fn lend_internal(
+        &mut self,
+        index: u16,
+        user_data: u64,
+        zero_copy: bool,
+    ) -> Result<(), LeaseError> {
+        let loan = self
+            .loans
+            .get_mut(usize::from(index))
+            .ok_or(LeaseError::UnknownBuffer)?;
+        if loan.is_some() {
+            return Err(LeaseError::Loaned);
+        }
+        if self.by_user_data.contains_key(&user_data) {
+            return Err(LeaseError::UserDataInUse);
+        }
+
+        *loan = Some(Loan {
+            user_data,
+            zero_copy,
+        });
+        self.by_user_data.insert(user_data, index);
+
+        Ok(())
+    }
+
+    /// Account for `cqe`, returning the index of the buffer it makes reusable, if any.
+    ///
+    /// Returns [`Option::None`] if no buffer is lent to the completion's `user_data`, or the
+    /// completion is for a zero-copy send whose notification is still to come.
+    pub fn complete(&mut self, cqe: &io_uring_cqe) -> Option<u16> {
+        let user_data = cqe.user_data.u64_();
+        let index = *self.by_user_data.get(&user_data)?;
+        let slot = &mut self.loans[usize::from(index)];
+
+        // a zero-copy send completes once with its result, flagged with `MORE` if the
+        // notification releasing the buffer follows
+        if slot.is_some_and(|loan| loan.zero_copy)
+            && !cqe.flags.contains(IoringCqeFlags::NOTIF)
+            && cqe.flags.contains(IoringCqeFlags::MORE)
+        {
+            return None;
+        }
+
+        debug_assert!(slot.is_some_and(|loan| loan.user_data == user_data));
+        *slot = None;
+        self.by_user_data.remove(&user_data);
+
+        Some(index)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rustix::io_uring::{IoringCqeFlags, io_uring_cqe, io_uring_user_data};
+
+    use super::{BufferLease, LeaseError};
+
+    fn cqe(user_data: u64, res: i32, flags: IoringCqeFlags) -> io_uring_cqe {
+        io_uring_cqe {
+            user_data: io_uring_user_data::from_u64(user_data),
+            res,
+            flags,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn zero_copy_buffer_is_reusable_only_after_notification() {
+        let mut lease = BufferLease::new(2);
+        lease.lend_zero_copy(1, 7).unwrap();
+
+        assert_eq!(lease.complete(&cqe(7, 16, IoringCqeFlags::MORE)), None);
+        assert!(lease.is_loaned(1));
+        assert_eq!(lease.lend(1, 8), Err(LeaseError::Loaned));
+
+        assert_eq!(lease.complete(&cqe(7, 0, IoringCqeFlags::NOTIF)), Some(1));
+        assert!(!lease.is_loaned(1));
+        assert_eq!(lease.lend(1, 8), Ok(()));
+    }
+
+    #[test]
+    fn zero_copy_buffer_is_reusable_when_no_notification_follows() {
+        let mut lease = BufferLease::new(1);
+        lease.lend_zero_copy(0, 7).unwrap();
+
+        assert_eq!(
+            lease.complete(&cqe(7, -11, IoringCqeFlags::empty())),
+            Some(0)
+        );
+        assert!(!lease.is_loaned(0));
+    }
+
+    #[test]
+    fn fixed_buffer_is_reusable_after_short_completion() {
+        let mut lease = BufferLease::new(1);
+        lease.lend(0, 7).unwrap();
+
+        assert_eq!(lease.complete(&cqe(7, 3, IoringCqeFlags::empty())), Some(0));
+        assert!(!lease.is_loaned(0));
+    }
+
+    #[test]
+    fn lend_rejects_unknown_buffers_and_reused_user_data() {
+        let mut lease = BufferLease::new(2);
+
+        assert_eq!(lease.lend(2, 7), Err(LeaseError::UnknownBuffer));
+        lease.lend(0, 7).unwrap();
+        assert_eq!(lease.lend_zero_copy(1, 7), Err(LeaseError::UserDataInUse));
+        assert!(!lease.is_loaned(1));
+    }
+
+    #[test]
+    fn unrelated_completions_release_nothing() {
+        let mut lease = BufferLease::new(1);
+        lease.lend(0, 7).unwrap();
+
+        assert_eq!(lease.complete(&cqe(8, 0, IoringCqeFlags::empty())), None);
+        assert!(lease.is_loaned(0));
+    }
+}
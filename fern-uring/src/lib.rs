@@ -1,6 +1,14 @@
 //! An implementation of `io_uring` for Linux
 
+pub mod flags;
+pub mod gate;
+pub mod lease;
+pub mod op;
+pub use op::{Op, OpName};
 pub mod params;
+pub mod prelude;
+pub mod registry;
+pub mod restrictions;
 pub mod ring_buffer;
 pub use ring_buffer::*;
 pub(crate) mod sync;
@@ -0,0 +1,116 @@
+//! Opcodes of submitted operations.
+//!
+//! [`Op`] is re-exported so callers don't need to depend on `rustix` directly to name an
+//! operation, and [`OpName`] gives each a printable name for logging.
+
+/// The opcode of a submitted operation.
+///
+/// Use [`OpName::name`] to get its name, which needs the trait in scope, such as through
+/// [`prelude`](crate::prelude).
+pub use rustix::io_uring::IoringOp as Op;
+
+/// Names an [`Op`], for logging.
+pub trait OpName {
+    /// Get the name of the operation, as in its `IORING_OP_*` constant without the prefix.
+    ///
+    /// Opcodes newer than this crate are named `"UNKNOWN"`.
+    fn name(&self) -> &'static str;
+}
+
+impl OpName for Op {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Nop => "NOP",
+            Self::Accept => "ACCEPT",
+            Self::AsyncCancel => "ASYNC_CANCEL",
+            Self::Close => "CLOSE",
+            Self::Connect => "CONNECT",
+            Self::EpollCtl => "EPOLL_CTL",
+            Self::Fadvise => "FADVISE",
+            Self::Fallocate => "FALLOCATE",
+            Self::FilesUpdate => "FILES_UPDATE",
+            Self::Fsync => "FSYNC",
+            Self::Linkat => "LINKAT",
+            Self::LinkTimeout => "LINK_TIMEOUT",
+            Self::Madvise => "MADVISE",
+            Self::Mkdirat => "MKDIRAT",
+            Self::Openat => "OPENAT",
+            Self::Openat2 => "OPENAT2",
+            Self::PollAdd => "POLL_ADD",
+            Self::PollRemove => "POLL_REMOVE",
+            Self::ProvideBuffers => "PROVIDE_BUFFERS",
+            Self::Read => "READ",
+            Self::Readv => "READV",
+            Self::ReadFixed => "READ_FIXED",
+            Self::Recv => "RECV",
+            Self::Recvmsg => "RECVMSG",
+            Self::RemoveBuffers => "REMOVE_BUFFERS",
+            Self::Renameat => "RENAMEAT",
+            Self::Send => "SEND",
+            Self::Sendmsg => "SENDMSG",
+            Self::Shutdown => "SHUTDOWN",
+            Self::Splice => "SPLICE",
+            Self::Statx => "STATX",
+            Self::Symlinkat => "SYMLINKAT",
+            Self::SyncFileRange => "SYNC_FILE_RANGE",
+            Self::Tee => "TEE",
+            Self::Timeout => "TIMEOUT",
+            Self::TimeoutRemove => "TIMEOUT_REMOVE",
+            Self::Unlinkat => "UNLINKAT",
+            Self::Write => "WRITE",
+            Self::Writev => "WRITEV",
+            Self::WriteFixed => "WRITE_FIXED",
+            Self::MsgRing => "MSG_RING",
+            Self::Fsetxattr => "FSETXATTR",
+            Self::Setxattr => "SETXATTR",
+            Self::Fgetxattr => "FGETXATTR",
+            Self::Getxattr => "GETXATTR",
+            Self::Socket => "SOCKET",
+            Self::UringCmd => "URING_CMD",
+            Self::SendZc => "SEND_ZC",
+            Self::SendmsgZc => "SENDMSG_ZC",
+            Self::ReadMultishot => "READ_MULTISHOT",
+            Self::Waitid => "WAITID",
+            Self::FutexWait => "FUTEX_WAIT",
+            Self::FutexWake => "FUTEX_WAKE",
+            Self::FutexWaitv => "FUTEX_WAITV",
+            Self::FixedFdInstall => "FIXED_FD_INSTALL",
+            Self::Ftruncate => "FTRUNCATE",
+            Self::Bind => "BIND",
+            Self::Listen => "LISTEN",
+            _ => "UNKNOWN",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Op, OpName};
+
+    #[test]
+    fn names_well_known_opcodes() {
+        assert_eq!(Op::Nop.name(), "NOP");
+        assert_eq!(Op::Readv.name(), "READV");
+        assert_eq!(Op::Write.name(), "WRITE");
+        assert_eq!(Op::AsyncCancel.name(), "ASYNC_CANCEL");
+        assert_eq!(Op::ReadMultishot.name(), "READ_MULTISHOT");
+    }
+
+    #[test]
+    fn names_are_unique() {
+        let ops = [
+            Op::Nop,
+            Op::Read,
+            Op::Readv,
+            Op::ReadFixed,
+            Op::Write,
+            Op::Writev,
+            Op::WriteFixed,
+        ];
+        let mut names: Vec<_> = ops.iter().map(OpName::name).collect();
+        names.sort_unstable();
+        names.dedup();
+
+        assert_eq!(names.len(), ops.len());
+    }
+}
@@ -1,11 +1,38 @@
 //! Parameters for an `IoUring` instance.
 
-use rustix::fd::{AsRawFd, BorrowedFd};
+use std::fmt::Display;
+use std::hash::{Hash, Hasher};
+
+use rustix::fd::{AsRawFd, BorrowedFd, RawFd};
 use rustix::io_uring::{IoringSetupFlags, io_uring_params};
 
 #[cfg(doc)]
 use rustix::io_uring::IoringSqFlags;
 
+/// Errors that occur as a result of configuring [`Params`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParamsError {
+    /// The fd is negative, so cannot refer to an open file.
+    NegativeFd,
+}
+
+impl ParamsError {
+    /// Get a description of the error, without going through the formatting machinery.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::NegativeFd => "The fd is negative, so cannot refer to an open file.",
+        }
+    }
+}
+
+impl Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Configures the Linux kernel, SQ, CQ, and how `io_uring` handles certain
 /// operations. Some options may result in performance improvements under
 /// specific circumstances.
@@ -73,36 +100,63 @@ impl Params {
     }
 
     /// Specify the number of submission queue entries. May be rounded to the next power of two.
+    ///
+    /// Sizes are clamped to the kernel's maximum of `32768` entries, which it would otherwise
+    /// reject. If a CQ size was already given with [`Self::with_cq_size`], it is raised to the
+    /// SQ size if smaller.
     #[must_use]
     pub const fn with_sq_size(mut self, sq_size: u32) -> Self {
-        self.0.sq_entries = sq_size.next_power_of_two();
+        self.0.sq_entries = saturating_next_power_of_two(sq_size, MAX_SQ_ENTRIES);
+        if self.0.flags.contains(IoringSetupFlags::CQSIZE) && self.0.cq_entries < self.0.sq_entries
+        {
+            self.0.cq_entries = self.0.sq_entries;
+        }
 
         self
     }
 
-    /// Specify the number of completion queue entries. Must be greater than the number of SQ
-    /// entries, and may be rounded to the next power of two.
+    /// Specify the number of completion queue entries. May be rounded to the next power of two.
+    ///
+    /// The kernel rejects a CQ smaller than the SQ, so the size is raised to the number of SQ
+    /// entries if smaller. Sizes are clamped to the kernel's maximum of `65536` entries, which it
+    /// would otherwise reject.
     #[must_use]
     pub const fn with_cq_size(mut self, cq_size: u32) -> Self {
         self.0.flags = self.0.flags.union(IoringSetupFlags::CQSIZE);
-        self.0.cq_entries = cq_size.next_power_of_two();
+        self.0.cq_entries = saturating_next_power_of_two(cq_size, MAX_CQ_ENTRIES);
+        if self.0.cq_entries < self.0.sq_entries {
+            self.0.cq_entries = self.0.sq_entries;
+        }
 
         self
     }
 
     /// Share the asynchronous worker thread backend of the specified `ring_fd` `io_uring`
     /// instance. The polling thread will also be shared, if both rings are setup with [`Self::with_sq_poll`].
-    // we check that the fd can't be negative, which should also be impossible with a BorrowedFd
-    #[allow(clippy::cast_sign_loss)]
     #[must_use]
     pub fn with_attached_work_queue(mut self, ring_fd: BorrowedFd) -> Self {
-        let raw_fd = ring_fd.as_raw_fd();
         self.0.flags = self.0.flags.union(IoringSetupFlags::ATTACH_WQ);
-        self.0.wq_fd = raw_fd;
+        self.0.wq_fd = ring_fd.as_raw_fd();
 
         self
     }
 
+    /// Like [`Self::with_attached_work_queue`], but takes the `ring_fd` as a raw fd, such as one
+    /// received from another process.
+    ///
+    /// # Errors
+    /// - if `ring_fd` is negative, returns [`ParamsError::NegativeFd`].
+    pub fn try_with_attached_work_queue(mut self, ring_fd: RawFd) -> Result<Self, ParamsError> {
+        if ring_fd < 0 {
+            return Err(ParamsError::NegativeFd);
+        }
+
+        self.0.flags = self.0.flags.union(IoringSetupFlags::ATTACH_WQ);
+        self.0.wq_fd = ring_fd;
+
+        Ok(self)
+    }
+
     /// Sets up the ring in a disabled state.
     ///
     /// When disabled, restrictions can be registered, but submissions are not allowed. The ring
@@ -183,13 +237,183 @@ impl Params {
 
         self
     }
+
+    /// Use 128-byte SQEs, rather than the default of 64 bytes.
+    ///
+    /// Required for operations which carry a larger payload in the SQE, such as some
+    /// `IORING_OP_URING_CMD` commands.
+    ///
+    /// Available since Linux 5.19
+    #[must_use]
+    pub const fn with_sqe128(mut self) -> Self {
+        self.0.flags = self.0.flags.union(IoringSetupFlags::SQE128);
+
+        self
+    }
+
+    /// Use 32-byte CQEs, rather than the default of 16 bytes.
+    ///
+    /// Required for operations which post a larger payload in the CQE, such as some
+    /// `IORING_OP_URING_CMD` commands.
+    ///
+    /// Available since Linux 5.19
+    #[must_use]
+    pub const fn with_cqe32(mut self) -> Self {
+        self.0.flags = self.0.flags.union(IoringSetupFlags::CQE32);
+
+        self
+    }
+
+    /// Get the setup flags which will be requested from the kernel.
+    #[must_use]
+    pub const fn setup_flags(&self) -> IoringSetupFlags {
+        self.0.flags
+    }
+
+    /// Get the names of the setup flags which will be requested from the kernel, for logging.
+    ///
+    /// Names match the `IORING_SETUP_*` constants without the prefix.
+    #[must_use]
+    pub fn flag_names(&self) -> Vec<&'static str> {
+        self.0.flags.iter_names().map(|(name, _)| name).collect()
+    }
+
+    /// Estimate how much memory the kernel will allocate for the rings of this configuration.
+    ///
+    /// This follows the kernel's layout of the rings: the CQEs follow a cache line of ring
+    /// headers, and the SQ index array follows the CQEs on the next cache line. With
+    /// [`Self::with_cqe32`], the kernel doubles the size of the headers along with the CQEs. When
+    /// no CQ size was given, the CQ has twice as many entries as the SQ, as the kernel would
+    /// choose.
+    #[must_use]
+    pub const fn estimated_memory(&self) -> MemoryEstimate {
+        const CACHE_LINE: usize = 64;
+
+        let flags = self.0.flags;
+        let sq_entries = self.0.sq_entries as usize;
+        let cq_entries = if flags.contains(IoringSetupFlags::CQSIZE) {
+            self.0.cq_entries as usize
+        } else {
+            sq_entries * 2
+        };
+        let sqe_size = if flags.contains(IoringSetupFlags::SQE128) {
+            128
+        } else {
+            64
+        };
+
+        // the kernel sizes the headers and CQEs as if each CQE were 16 bytes, then doubles the
+        // whole region for 32-byte CQEs
+        let mut cq_ring = CACHE_LINE + cq_entries * 16;
+        if flags.contains(IoringSetupFlags::CQE32) {
+            cq_ring *= 2;
+        }
+        let sq_ring = if flags.contains(IoringSetupFlags::NO_SQARRAY) {
+            cq_ring
+        } else {
+            cq_ring.next_multiple_of(CACHE_LINE) + sq_entries * size_of::<u32>()
+        };
+
+        MemoryEstimate {
+            sq_ring,
+            cq_ring,
+            sqes: sq_entries * sqe_size,
+        }
+    }
+}
+
+// `IORING_MAX_ENTRIES` and `IORING_MAX_CQ_ENTRIES`, beyond which the kernel rejects a ring
+const MAX_SQ_ENTRIES: u32 = 32768;
+const MAX_CQ_ENTRIES: u32 = 2 * MAX_SQ_ENTRIES;
+
+/// Round `size` up to the next power of two, clamped to `max`, which must be a power of two.
+const fn saturating_next_power_of_two(size: u32, max: u32) -> u32 {
+    if size > max {
+        max
+    } else {
+        size.next_power_of_two()
+    }
+}
+
+// Only the fields an application configures are compared. `resv` may be used by newer kernels,
+// and `features`, `sq_off` and `cq_off` are written by the kernel during setup, so none of them
+// say anything about the requested configuration.
+impl PartialEq for Params {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.sq_entries == other.0.sq_entries
+            && self.0.cq_entries == other.0.cq_entries
+            && self.0.flags == other.0.flags
+            && self.0.sq_thread_cpu == other.0.sq_thread_cpu
+            && self.0.sq_thread_idle == other.0.sq_thread_idle
+            && self.0.wq_fd == other.0.wq_fd
+    }
+}
+
+impl Eq for Params {}
+
+impl Hash for Params {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.sq_entries.hash(state);
+        self.0.cq_entries.hash(state);
+        self.0.flags.hash(state);
+        self.0.sq_thread_cpu.hash(state);
+        self.0.sq_thread_idle.hash(state);
+        self.0.wq_fd.hash(state);
+    }
+}
+
+/// The memory the kernel allocates for a ring, as estimated by [`Params::estimated_memory`].
+///
+/// Sizes are in bytes, before being rounded up to whole pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryEstimate {
+    sq_ring: usize,
+    cq_ring: usize,
+    sqes: usize,
+}
+
+impl MemoryEstimate {
+    /// Get the size of the SQ ring, including the SQ index array.
+    #[must_use]
+    pub const fn sq_ring(&self) -> usize {
+        self.sq_ring
+    }
+
+    /// Get the size of the CQ ring, including its CQEs.
+    #[must_use]
+    pub const fn cq_ring(&self) -> usize {
+        self.cq_ring
+    }
+
+    /// Get the size of the SQE array.
+    #[must_use]
+    pub const fn sqes(&self) -> usize {
+        self.sqes
+    }
+
+    /// Get the total size of the rings.
+    ///
+    /// The SQ and CQ rings share a single allocation, so only the larger of the two is counted.
+    #[must_use]
+    pub const fn total(&self) -> usize {
+        let rings = if self.sq_ring > self.cq_ring {
+            self.sq_ring
+        } else {
+            self.cq_ring
+        };
+
+        rings + self.sqes
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use rustix::{fd::BorrowedFd, io_uring::IoringSetupFlags};
+    use std::hash::{DefaultHasher, Hash, Hasher};
+
+    use rustix::fd::BorrowedFd;
+    use rustix::io_uring::{IoringFeatureFlags, IoringSetupFlags};
 
-    use super::Params;
+    use super::{Params, ParamsError};
 
     // Ensure we only run tests that the host machine can support
     // fn at_least_kernel_version(version: &str) -> bool {
@@ -251,6 +475,14 @@ mod test {
                 func: |p: Params| p.with_deferred_taskrun(),
                 flags: vec![IoringSetupFlags::DEFER_TASKRUN],
             },
+            TestCase {
+                func: |p: Params| p.with_sqe128(),
+                flags: vec![IoringSetupFlags::SQE128],
+            },
+            TestCase {
+                func: |p: Params| p.with_cqe32(),
+                flags: vec![IoringSetupFlags::CQE32],
+            },
         ];
 
         // check individual function calls against flags
@@ -297,12 +529,46 @@ mod test {
         assert_eq!(params.0.sq_entries, 4);
     }
 
+    #[test]
+    fn clamps_sizes_to_kernel_maximum() {
+        let params = Params::new().with_sq_size(u32::MAX).with_cq_size(u32::MAX);
+        assert_eq!(params.0.sq_entries, 32768);
+        assert_eq!(params.0.cq_entries, 65536);
+
+        let params = Params::new().with_sq_size(32769).with_cq_size(65537);
+        assert_eq!(params.0.sq_entries, 32768);
+        assert_eq!(params.0.cq_entries, 65536);
+
+        let params = Params::new().with_sq_size(32768).with_cq_size(65536);
+        assert_eq!(params.0.sq_entries, 32768);
+        assert_eq!(params.0.cq_entries, 65536);
+
+        let params = Params::new().with_sq_size(16385).with_cq_size(32769);
+        assert_eq!(params.0.sq_entries, 32768);
+        assert_eq!(params.0.cq_entries, 65536);
+    }
+
     #[test]
     fn sets_cq_size() {
         let params = Params::new().with_cq_size(2);
         assert_eq!(params.0.cq_entries, 2);
     }
 
+    #[test]
+    fn raises_cq_size_to_sq_size() {
+        let params = Params::new().with_sq_size(64).with_cq_size(32);
+        assert_eq!(params.0.cq_entries, 64);
+
+        let params = Params::new().with_cq_size(32).with_sq_size(64);
+        assert_eq!(params.0.cq_entries, 64);
+    }
+
+    #[test]
+    fn keeps_cq_size_larger_than_sq_size() {
+        let params = Params::new().with_cq_size(128).with_sq_size(64);
+        assert_eq!(params.0.cq_entries, 128);
+    }
+
     #[test]
     fn sets_cq_size_to_next_power_of_two() {
         let params = Params::new().with_cq_size(3);
@@ -318,6 +584,125 @@ mod test {
         assert_eq!(params.0.wq_fd, raw_fd);
     }
 
+    #[test]
+    fn sets_attached_work_queue_from_raw_fd() {
+        let params = Params::new().try_with_attached_work_queue(3).unwrap();
+        assert!(params.setup_flags().contains(IoringSetupFlags::ATTACH_WQ));
+        assert_eq!(params.0.wq_fd, 3);
+    }
+
+    #[test]
+    fn rejects_negative_attached_work_queue() {
+        assert_eq!(
+            Params::new().try_with_attached_work_queue(-1),
+            Err(ParamsError::NegativeFd)
+        );
+    }
+
+    #[test]
+    fn estimates_memory_for_default_entry_sizes() {
+        let estimate = Params::new().with_sq_size(32).estimated_memory();
+
+        // 64 CQEs of 16 bytes after the ring headers, then 32 indices on the next cache line
+        assert_eq!(estimate.cq_ring(), 64 + 64 * 16);
+        assert_eq!(estimate.sq_ring(), 64 + 64 * 16 + 32 * 4);
+        assert_eq!(estimate.sqes(), 32 * 64);
+        assert_eq!(estimate.total(), 64 + 64 * 16 + 32 * 4 + 32 * 64);
+    }
+
+    #[test]
+    fn estimates_memory_for_big_entries() {
+        let estimate = Params::new()
+            .with_sq_size(32)
+            .with_cq_size(128)
+            .with_sqe128()
+            .with_cqe32()
+            .estimated_memory();
+
+        assert_eq!(estimate.cq_ring(), 2 * (64 + 128 * 16));
+        assert_eq!(estimate.sq_ring(), 2 * (64 + 128 * 16) + 32 * 4);
+        assert_eq!(estimate.sqes(), 32 * 128);
+    }
+
+    #[test]
+    fn estimates_memory_for_cqe32_by_doubling_headers() {
+        let estimate = Params::new()
+            .with_sq_size(32)
+            .with_cqe32()
+            .estimated_memory();
+
+        // the kernel places the SQ index array at byte 2176 of this ring, rather than 2112
+        assert_eq!(estimate.cq_ring(), 2176);
+        assert_eq!(estimate.sq_ring(), 2176 + 32 * 4);
+        assert_eq!(estimate.total(), 2176 + 32 * 4 + 32 * 64);
+    }
+
+    #[test]
+    fn estimates_memory_without_sq_array() {
+        let mut params = Params::new().with_sq_size(8);
+        params.0.flags |= IoringSetupFlags::NO_SQARRAY;
+
+        let estimate = params.estimated_memory();
+
+        assert_eq!(estimate.sq_ring(), estimate.cq_ring());
+        assert_eq!(estimate.total(), 64 + 16 * 16 + 8 * 64);
+    }
+
+    #[test]
+    fn reports_exactly_the_requested_flags() {
+        let params = Params::new()
+            .with_sq_size(32)
+            .with_cq_size(64)
+            .with_single_issuer()
+            .with_deferred_taskrun();
+
+        assert_eq!(
+            params.setup_flags(),
+            IoringSetupFlags::CQSIZE
+                | IoringSetupFlags::SINGLE_ISSUER
+                | IoringSetupFlags::DEFER_TASKRUN
+        );
+        assert_eq!(
+            params.flag_names(),
+            ["CQSIZE", "SINGLE_ISSUER", "DEFER_TASKRUN"]
+        );
+    }
+
+    #[test]
+    fn reports_no_flags_by_default() {
+        let params = Params::new();
+
+        assert!(params.setup_flags().is_empty());
+        assert!(params.flag_names().is_empty());
+    }
+
+    #[test]
+    fn equality_ignores_reserved_and_kernel_written_fields() {
+        let params = Params::new().with_sq_size(32).with_single_issuer();
+        let mut from_newer_kernel = params;
+        from_newer_kernel.0.resv[0] = 1;
+        from_newer_kernel.0.features = IoringFeatureFlags::SINGLE_MMAP;
+        from_newer_kernel.0.sq_off.array = 64;
+
+        assert_eq!(params, from_newer_kernel);
+        assert_eq!(hash(&params), hash(&from_newer_kernel));
+    }
+
+    #[test]
+    fn equality_compares_configured_fields() {
+        let params = Params::new().with_sq_size(32);
+
+        assert_ne!(params, params.with_sq_size(64));
+        assert_ne!(params, params.with_single_issuer());
+        assert_ne!(params, params.with_sq_poll(Some(10)));
+    }
+
+    fn hash(params: &Params) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        params.hash(&mut hasher);
+        hasher.finish()
+    }
+
     #[test]
     fn nicely_chained_function_calls_compiles() {
         let params = Params::new()
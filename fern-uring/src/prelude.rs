@@ -0,0 +1,13 @@
+//! Traits which extend types from other crates, such as naming an [`Op`](crate::Op).
+//!
+//! Their methods can only be called once the trait is in scope, so glob importing the prelude
+//! brings them all in at once:
+//!
+//! ```
+//! use fern_uring::Op;
+//! use fern_uring::prelude::*;
+//!
+//! assert_eq!(Op::Readv.name(), "READV");
+//! ```
+
+pub use crate::op::OpName;
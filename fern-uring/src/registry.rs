@@ -0,0 +1,182 @@
+//! Correlates completions with the submissions that produced them.
+//!
+//! Completions only identify their submission through `user_data`. A [`SubmissionRegistry`]
+//! records what each submission was when it is made, and hands that description back when the
+//! matching completion is reaped.
+
+use std::collections::HashMap;
+
+use rustix::io_uring::{IoringOp, io_uring_cqe};
+
+/// Describes a submission, so its completion can be identified.
+#[derive(Debug)]
+pub struct SubmissionMeta {
+    opcode: IoringOp,
+    buffer: Option<Vec<u8>>,
+}
+
+impl SubmissionMeta {
+    /// Create a new `SubmissionMeta` for an operation with the given `opcode`.
+    #[must_use]
+    pub const fn new(opcode: IoringOp) -> Self {
+        Self {
+            opcode,
+            buffer: None,
+        }
+    }
+
+    /// Attach a buffer owned by the operation, which must be kept alive until it completes.
+    #[must_use]
+    pub fn with_buffer(mut self, buffer: Vec<u8>) -> Self {
+        self.buffer = Some(buffer);
+
+        self
+    }
+
+    /// Get the opcode of the submitted operation.
+    #[must_use]
+    pub const fn opcode(&self) -> IoringOp {
+        self.opcode
+    }
+
+    /// Get the buffer owned by the operation, if any.
+    #[must_use]
+    pub fn buffer(&self) -> Option<&[u8]> {
+        self.buffer.as_deref()
+    }
+
+    /// Take ownership of the buffer owned by the operation, if any.
+    #[must_use]
+    pub fn into_buffer(self) -> Option<Vec<u8>> {
+        self.buffer
+    }
+}
+
+/// Records metadata for in-flight submissions, keyed by `user_data`.
+#[derive(Debug)]
+pub struct SubmissionRegistry<M = SubmissionMeta> {
+    submissions: HashMap<u64, M>,
+}
+
+impl<M> Default for SubmissionRegistry<M> {
+    fn default() -> Self {
+        Self {
+            submissions: HashMap::new(),
+        }
+    }
+}
+
+impl<M> SubmissionRegistry<M> {
+    /// Create a new, empty `SubmissionRegistry`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the number of submissions awaiting completion.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.submissions.len()
+    }
+
+    /// Determines if no submissions are awaiting completion.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.submissions.is_empty()
+    }
+
+    /// Record `meta` for the submission identified by `user_data`.
+    ///
+    /// If a submission with the same `user_data` was already recorded, its metadata is replaced and
+    /// returned.
+    pub fn record(&mut self, user_data: u64, meta: M) -> Option<M> {
+        self.submissions.insert(user_data, meta)
+    }
+
+    /// Get the metadata recorded for the submission identified by `user_data`, without removing it.
+    ///
+    /// Multishot operations produce a completion flagged with `IORING_CQE_F_MORE` for every
+    /// result but the last. Use this to identify those, and [`Self::complete`] once the final
+    /// completion arrives.
+    #[must_use]
+    pub fn get(&self, user_data: u64) -> Option<&M> {
+        self.submissions.get(&user_data)
+    }
+
+    /// Remove and return the metadata recorded for the submission that produced `cqe`.
+    ///
+    /// Returns [`Option::None`] if no submission was recorded with the completion's `user_data`.
+    pub fn complete(&mut self, cqe: &io_uring_cqe) -> Option<M> {
+        self.submissions.remove(&cqe.user_data.u64_())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rustix::io_uring::{IoringOp, io_uring_cqe, io_uring_user_data};
+
+    use super::{SubmissionMeta, SubmissionRegistry};
+
+    fn cqe(user_data: u64) -> io_uring_cqe {
+        io_uring_cqe {
+            user_data: io_uring_user_data::from_u64(user_data),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn completions_resolve_to_their_submission() {
+        let mut registry = SubmissionRegistry::new();
+        registry.record(
+            1,
+            SubmissionMeta::new(IoringOp::Read).with_buffer(vec![0; 16]),
+        );
+        registry.record(2, SubmissionMeta::new(IoringOp::Timeout));
+
+        let timeout = registry.complete(&cqe(2)).unwrap();
+        assert_eq!(timeout.opcode(), IoringOp::Timeout);
+        assert!(timeout.buffer().is_none());
+
+        let read = registry.complete(&cqe(1)).unwrap();
+        assert_eq!(read.opcode(), IoringOp::Read);
+        assert_eq!(read.into_buffer().unwrap().len(), 16);
+
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn completing_removes_the_submission() {
+        let mut registry = SubmissionRegistry::new();
+        registry.record(1, SubmissionMeta::new(IoringOp::Nop));
+
+        assert!(registry.complete(&cqe(1)).is_some());
+        assert!(registry.complete(&cqe(1)).is_none());
+    }
+
+    #[test]
+    fn unknown_completions_resolve_to_nothing() {
+        let mut registry = SubmissionRegistry::<SubmissionMeta>::new();
+
+        assert!(registry.complete(&cqe(1)).is_none());
+    }
+
+    #[test]
+    fn get_does_not_remove_the_submission() {
+        let mut registry = SubmissionRegistry::new();
+        registry.record(1, SubmissionMeta::new(IoringOp::ReadMultishot));
+
+        assert_eq!(registry.get(1).unwrap().opcode(), IoringOp::ReadMultishot);
+        assert_eq!(registry.len(), 1);
+    }
+
+    #[test]
+    fn recording_the_same_user_data_replaces_the_submission() {
+        let mut registry = SubmissionRegistry::new();
+        registry.record(1, SubmissionMeta::new(IoringOp::Nop));
+
+        let previous = registry.record(1, SubmissionMeta::new(IoringOp::Read));
+
+        assert_eq!(previous.unwrap().opcode(), IoringOp::Nop);
+        assert_eq!(registry.get(1).unwrap().opcode(), IoringOp::Read);
+    }
+}
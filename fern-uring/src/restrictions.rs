@@ -0,0 +1,184 @@
+//! Restrictions limiting what a disabled `IoUring` instance may be used for.
+//!
+//! Restrictions are registered while the ring is disabled (see
+//! [`Params::with_disabled_ring`](crate::params::Params::with_disabled_ring)), and take effect
+//! once it is enabled. Anything not explicitly allowed is then rejected by the kernel.
+
+use std::fmt;
+
+use rustix::io_uring::{
+    IoringOp, IoringRegisterOp, IoringRestrictionOp, IoringSqeFlags, io_uring_restriction,
+};
+
+/// Builds the list of `io_uring_restriction` entries to register with the kernel.
+///
+/// Each method appends one restriction, tagged with the matching [`IoringRestrictionOp`], so the
+/// union inside the raw struct never has to be filled in by hand.
+#[derive(Default, Clone)]
+pub struct RestrictionBuilder {
+    restrictions: Vec<io_uring_restriction>,
+}
+
+impl RestrictionBuilder {
+    /// Create a new `RestrictionBuilder`, which allows nothing.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow submissions using the `op` opcode.
+    #[must_use]
+    pub fn allow_op(mut self, op: IoringOp) -> Self {
+        let mut restriction = io_uring_restriction::default();
+        restriction.opcode = IoringRestrictionOp::SqeOp;
+        restriction.register_or_sqe_op_or_sqe_flags.sqe_op = op;
+        self.restrictions.push(restriction);
+
+        self
+    }
+
+    /// Allow `io_uring_register` calls using the `op` opcode.
+    #[must_use]
+    pub fn allow_register(mut self, op: IoringRegisterOp) -> Self {
+        let mut restriction = io_uring_restriction::default();
+        restriction.opcode = IoringRestrictionOp::RegisterOp;
+        restriction.register_or_sqe_op_or_sqe_flags.register_op = op;
+        self.restrictions.push(restriction);
+
+        self
+    }
+
+    /// Require every submission to set all of `flags`.
+    #[must_use]
+    pub fn require_sqe_flags(mut self, flags: IoringSqeFlags) -> Self {
+        let mut restriction = io_uring_restriction::default();
+        restriction.opcode = IoringRestrictionOp::SqeFlagsRequired;
+        restriction.register_or_sqe_op_or_sqe_flags.sqe_flags = flags;
+        self.restrictions.push(restriction);
+
+        self
+    }
+
+    /// Allow submissions to set any of `flags`, in addition to those that are required.
+    #[must_use]
+    pub fn allow_sqe_flags(mut self, flags: IoringSqeFlags) -> Self {
+        let mut restriction = io_uring_restriction::default();
+        restriction.opcode = IoringRestrictionOp::SqeFlagsAllowed;
+        restriction.register_or_sqe_op_or_sqe_flags.sqe_flags = flags;
+        self.restrictions.push(restriction);
+
+        self
+    }
+
+    /// Get the restrictions built so far.
+    #[must_use]
+    pub fn as_slice(&self) -> &[io_uring_restriction] {
+        &self.restrictions
+    }
+
+    /// Consume the builder, producing the restrictions to register.
+    #[must_use]
+    pub fn build(self) -> Vec<io_uring_restriction> {
+        self.restrictions
+    }
+}
+
+impl fmt::Debug for RestrictionBuilder {
+    // `io_uring_restriction` holds a union, so only the tag of each restriction can be shown.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(
+                self.restrictions
+                    .iter()
+                    .map(|restriction| restriction.opcode),
+            )
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rustix::io_uring::{IoringOp, IoringRegisterOp, IoringRestrictionOp, IoringSqeFlags};
+
+    use super::RestrictionBuilder;
+
+    #[test]
+    fn allows_nothing_by_default() {
+        assert!(RestrictionBuilder::new().build().is_empty());
+    }
+
+    #[test]
+    fn allow_op_tags_sqe_op() {
+        let restrictions = RestrictionBuilder::new().allow_op(IoringOp::Read).build();
+
+        assert_eq!(restrictions.len(), 1);
+        assert_eq!(restrictions[0].opcode, IoringRestrictionOp::SqeOp);
+        // SAFETY: `SqeOp` restrictions hold `sqe_op`.
+        let op = unsafe { restrictions[0].register_or_sqe_op_or_sqe_flags.sqe_op };
+        assert_eq!(op, IoringOp::Read);
+    }
+
+    #[test]
+    fn allow_register_tags_register_op() {
+        let restrictions = RestrictionBuilder::new()
+            .allow_register(IoringRegisterOp::RegisterBuffers)
+            .build();
+
+        assert_eq!(restrictions.len(), 1);
+        assert_eq!(restrictions[0].opcode, IoringRestrictionOp::RegisterOp);
+        // SAFETY: `RegisterOp` restrictions hold `register_op`.
+        let op = unsafe { restrictions[0].register_or_sqe_op_or_sqe_flags.register_op };
+        assert_eq!(op, IoringRegisterOp::RegisterBuffers);
+    }
+
+    #[test]
+    fn require_sqe_flags_tags_sqe_flags_required() {
+        let restrictions = RestrictionBuilder::new()
+            .require_sqe_flags(IoringSqeFlags::FIXED_FILE)
+            .build();
+
+        assert_eq!(restrictions.len(), 1);
+        assert_eq!(
+            restrictions[0].opcode,
+            IoringRestrictionOp::SqeFlagsRequired
+        );
+        // SAFETY: `SqeFlagsRequired` restrictions hold `sqe_flags`.
+        let flags = unsafe { restrictions[0].register_or_sqe_op_or_sqe_flags.sqe_flags };
+        assert_eq!(flags, IoringSqeFlags::FIXED_FILE);
+    }
+
+    #[test]
+    fn allow_sqe_flags_tags_sqe_flags_allowed() {
+        let restrictions = RestrictionBuilder::new()
+            .allow_sqe_flags(IoringSqeFlags::IO_LINK | IoringSqeFlags::ASYNC)
+            .build();
+
+        assert_eq!(restrictions.len(), 1);
+        assert_eq!(restrictions[0].opcode, IoringRestrictionOp::SqeFlagsAllowed);
+        // SAFETY: `SqeFlagsAllowed` restrictions hold `sqe_flags`.
+        let flags = unsafe { restrictions[0].register_or_sqe_op_or_sqe_flags.sqe_flags };
+        assert_eq!(flags, IoringSqeFlags::IO_LINK | IoringSqeFlags::ASYNC);
+    }
+
+    #[test]
+    fn keeps_restrictions_in_order() {
+        let builder = RestrictionBuilder::new()
+            .allow_op(IoringOp::Nop)
+            .allow_register(IoringRegisterOp::RegisterFiles)
+            .allow_sqe_flags(IoringSqeFlags::ASYNC);
+
+        let opcodes: Vec<_> = builder
+            .as_slice()
+            .iter()
+            .map(|restriction| restriction.opcode)
+            .collect();
+        assert_eq!(
+            opcodes,
+            [
+                IoringRestrictionOp::SqeOp,
+                IoringRestrictionOp::RegisterOp,
+                IoringRestrictionOp::SqeFlagsAllowed,
+            ]
+        );
+    }
+}
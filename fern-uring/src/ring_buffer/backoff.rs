@@ -0,0 +1,67 @@
+//! Strategies for waiting on a ring buffer that can't currently make progress.
+//!
+//! Spinning gives the lowest wake up latency, but burns CPU. Sleeping is cheap on CPU, but nothing
+//! wakes the thread early when progress becomes possible. A [`BackoffStrategy`] spins, then
+//! yields, then sleeps, so callers can tune the tradeoff.
+
+use std::time::Duration;
+
+use crate::sync::{hint, thread};
+
+/// Controls how a blocking operation waits between attempts.
+///
+/// The first `spins` attempts are separated by a spin loop hint, the next `yields` attempts yield
+/// the thread to the scheduler, and all further attempts sleep the thread for `sleep`. If a
+/// later phase is disabled, the last enabled phase repeats indefinitely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackoffStrategy {
+    spins: u32,
+    yields: u32,
+    sleep: Option<Duration>,
+}
+
+impl Default for BackoffStrategy {
+    fn default() -> Self {
+        Self::new(64, 16, Duration::from_micros(50))
+    }
+}
+
+impl BackoffStrategy {
+    /// Create a new `BackoffStrategy` that spins `spins` times, then yields `yields` times, then
+    /// sleeps for `sleep` between each further attempt.
+    #[must_use]
+    pub const fn new(spins: u32, yields: u32, sleep: Duration) -> Self {
+        Self {
+            spins,
+            yields,
+            sleep: Some(sleep),
+        }
+    }
+
+    /// Only ever spin between attempts. Lowest latency, highest CPU usage.
+    #[must_use]
+    pub const fn spin_only() -> Self {
+        Self {
+            spins: u32::MAX,
+            yields: 0,
+            sleep: None,
+        }
+    }
+
+    /// Sleep for `sleep` between every attempt. Highest latency, lowest CPU usage.
+    #[must_use]
+    pub const fn sleep_only(sleep: Duration) -> Self {
+        Self::new(0, 0, sleep)
+    }
+
+    /// Wait before making attempt number `step + 1`.
+    pub(crate) fn snooze(&self, step: u32) {
+        if step < self.spins || (self.yields == 0 && self.sleep.is_none()) {
+            hint::spin_loop();
+        } else if step - self.spins < self.yields || self.sleep.is_none() {
+            thread::yield_now();
+        } else if let Some(sleep) = self.sleep {
+            thread::sleep(sleep);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! State and validation shared by both sides of a ring buffer.
+//!
+//! [`RingBufferProducer`](super::RingBufferProducer) and
+//! [`RingBufferConsumer`](super::RingBufferConsumer) differ only in which index they advance, so
+//! the indices, entries and their layout live here, keeping the two sides from drifting apart.
+
+use std::sync::atomic::Ordering;
+
+use super::{ReservedBatch, RingBufferError, entry_start};
+use crate::sync::atomic::AtomicU32;
+
+#[derive(Debug)]
+pub(super) struct RingBufferCore<'ring, T> {
+    pub(super) head: &'ring AtomicU32,
+    pub(super) tail: &'ring AtomicU32,
+    pub(super) entries: &'ring [T],
+    // covers entries rather than slots, each entry spanning `1 << shift` slots
+    mask: u32,
+    shift: u32,
+}
+
+impl<'ring, T> RingBufferCore<'ring, T> {
+    /// Validate `entries` and `mask`, where `mask` covers every slot of `entries`. If `big` is
+    /// set, each entry spans two slots.
+    pub(super) fn new(
+        entries: &'ring [T],
+        head: &'ring AtomicU32,
+        tail: &'ring AtomicU32,
+        mask: u32,
+        big: bool,
+    ) -> Result<Self, RingBufferError> {
+        if entries.len() as u64 > u64::from(u32::MAX) {
+            return Err(RingBufferError::EntriesSliceTooLong);
+        }
+        if entries.len() >> u32::from(big) == 0 {
+            return Err(RingBufferError::EmptyRing);
+        }
+        if (entries.len() as u64).next_power_of_two() != entries.len() as u64 {
+            return Err(RingBufferError::LengthNotPowerOfTwo);
+        }
+        if mask as usize != entries.len() - 1 {
+            return Err(RingBufferError::InvalidMaskValue);
+        }
+
+        // `mask` covers every entry, but indices count large objects when `big` is set
+        let shift = u32::from(big);
+
+        Ok(Self {
+            head,
+            tail,
+            entries,
+            mask: mask >> shift,
+            shift,
+        })
+    }
+
+    /// Validate `entries` and `mask` for large objects spanning two slots, where `mask` covers
+    /// the large objects rather than every slot.
+    pub(super) fn new_big_checked(
+        entries: &'ring [T],
+        head: &'ring AtomicU32,
+        tail: &'ring AtomicU32,
+        mask: u32,
+    ) -> Result<Self, RingBufferError> {
+        // a mask too large to double can never match, and `u32::MAX` is never a valid mask over
+        // every entry, as `entries.len()` cannot be `1 << 32`
+        let entries_mask = u32::try_from((u64::from(mask) << 1) | 1).unwrap_or(u32::MAX);
+
+        Self::new(entries, head, tail, entries_mask, true)
+    }
+
+    pub(super) fn size(&self) -> usize {
+        self.entries.len() >> self.shift
+    }
+
+    pub(super) fn available(&self) -> u32 {
+        self.tail
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.head.load(Ordering::Acquire))
+    }
+
+    /// Get every slot making up the entry at `index`.
+    pub(super) fn entry(&self, index: u32) -> &'ring [T] {
+        let start = entry_start(index, self.mask, self.shift, self.entries.len());
+
+        &self.entries[start..start + (1 << self.shift)]
+    }
+
+    /// Get the first slot of the entry at `index`.
+    pub(super) fn slot(&self, index: u32) -> &'ring T {
+        &self.entries[entry_start(index, self.mask, self.shift, self.entries.len())]
+    }
+
+    pub(super) fn batch(&self, index: u32, len: u32) -> ReservedBatch<'ring, T> {
+        ReservedBatch::new(index, len, self.entries, self.mask, self.shift)
+    }
+}
@@ -2,11 +2,19 @@
 //!
 //! Reads from head -> tail. When an entry is no longer needed, it can be committed, where the head
 //! is incremented. The tail is assumed to be incremented by an external process (the kernel).
+//!
+//! Every advance of the shared head is a `Release` operation. As soon as the kernel observes the
+//! new head it may overwrite the entries behind it, so every read of those entries must happen
+//! before the head moves. A `Relaxed` advance would let the kernel overwrite a CQE that is still
+//! being read.
 
+use std::ops::Deref;
 use std::sync::atomic::Ordering;
 
-use super::{ReservedEntry, RingBufferError};
+use super::base::RingBufferCore;
+use super::{ReservedBatch, ReservedEntry, RingBufferError, RingBufferProducer};
 use crate::sync::atomic::AtomicU32;
+use crate::sync::hint;
 
 /// A thread-safe and lock-free ring buffer consumer.
 ///
@@ -14,18 +22,17 @@ use crate::sync::atomic::AtomicU32;
 /// is incremented. The tail is assumed to be incremented by an external process (the kernel).
 #[derive(Debug)]
 pub struct RingBufferConsumer<'ring, T> {
-    head: &'ring AtomicU32,
-    tail: &'ring AtomicU32,
+    core: RingBufferCore<'ring, T>,
     uncommitted_head: AtomicU32,
-    entries: &'ring [T],
-    mask: u32,
-    shift: u32,
 }
 
 impl<'ring, T> RingBufferConsumer<'ring, T> {
     /// Creates a new `RingBufferConsumer`, taking existing indicies for the head and tail.
     ///
+    /// `entries` must have a length of at least one.
+    ///
     /// # Errors
+    /// - if `entries` is empty, the [`RingBufferError::EmptyRing`] error is returned.
     /// - `entries.len()` must be a power of two. If this is not the case, the
     ///   [`RingBufferError::LengthNotPowerOfTwo`] error is returned.
     /// - `mask` must represent bits of a valid index into `entries`. If this is not the case, the
@@ -36,12 +43,16 @@ impl<'ring, T> RingBufferConsumer<'ring, T> {
         tail: &'ring AtomicU32,
         mask: u32,
     ) -> Result<Self, RingBufferError> {
-        Self::new_internal(entries, head, tail, mask, false)
+        RingBufferCore::new(entries, head, tail, mask, false).map(Self::from_core)
     }
 
     /// Creates a new `RingBufferConsumer` for large objects that span two entries, taking existing indicies for the head and tail.
     ///
+    /// `entries` must have a length of at least two, making up a single large object.
+    ///
     /// # Errors
+    /// - if `entries` has fewer than two elements, the [`RingBufferError::EmptyRing`] error is
+    ///   returned.
     /// - `entries.len()` must be a power of two. If this is not the case, the
     ///   [`RingBufferError::LengthNotPowerOfTwo`] error is returned.
     /// - `mask` must represent bits of a valid index into `entries`. If this is not the case, the
@@ -52,76 +63,136 @@ impl<'ring, T> RingBufferConsumer<'ring, T> {
         tail: &'ring AtomicU32,
         mask: u32,
     ) -> Result<Self, RingBufferError> {
-        Self::new_internal(entries, head, tail, mask, true)
+        RingBufferCore::new(entries, head, tail, mask, true).map(Self::from_core)
     }
 
-    fn new_internal(
+    /// Creates a new `RingBufferConsumer` for large objects that span two entries, where `mask` covers
+    /// the large objects rather than every entry, taking existing indicies for the head and tail.
+    ///
+    /// This matches the ring mask reported by the kernel for rings set up with 128-byte SQEs or
+    /// 32-byte CQEs, where `entries` holds two slots for every object the mask can index. Unlike
+    /// [`Self::new_big`], which takes a mask over every entry, this verifies `entries.len()` is
+    /// exactly twice the capacity described by `mask`.
+    ///
+    /// # Errors
+    /// - if `entries` has fewer than two elements, the [`RingBufferError::EmptyRing`] error is
+    ///   returned.
+    /// - if `entries.len()` is greater than `u32::MAX`, the
+    ///   [`RingBufferError::EntriesSliceTooLong`] error is returned.
+    /// - `entries.len()` must be a power of two. If this is not the case, the
+    ///   [`RingBufferError::LengthNotPowerOfTwo`] error is returned.
+    /// - `entries.len()` must be exactly `2 * (mask + 1)`. If this is not the case, the
+    ///   [`RingBufferError::InvalidMaskValue`] error is returned.
+    pub fn new_big_checked(
         entries: &'ring [T],
         head: &'ring AtomicU32,
         tail: &'ring AtomicU32,
         mask: u32,
-        big: bool,
     ) -> Result<Self, RingBufferError> {
-        if entries.len() as u64 > u64::from(u32::MAX) {
-            return Err(RingBufferError::EntriesSliceTooLong);
-        }
-        if (entries.len() as u64).next_power_of_two() != entries.len() as u64 {
-            return Err(RingBufferError::LengthNotPowerOfTwo);
-        }
-        if mask as usize != entries.len() - 1 {
-            return Err(RingBufferError::InvalidMaskValue);
-        }
+        RingBufferCore::new_big_checked(entries, head, tail, mask).map(Self::from_core)
+    }
 
-        let uncommitted_head = AtomicU32::new(head.load(Ordering::Relaxed));
+    fn from_core(core: RingBufferCore<'ring, T>) -> Self {
+        let uncommitted_head = AtomicU32::new(core.head.load(Ordering::Relaxed));
 
-        Ok(Self {
-            head,
-            tail,
+        Self {
+            core,
             uncommitted_head,
-            entries,
-            mask,
-            shift: u32::from(big),
-        })
+        }
     }
 
     /// Get the size of the ring buffer.
     #[must_use]
     pub fn size(&self) -> usize {
-        self.entries.len() >> self.shift
+        self.core.size()
     }
 
     /// Get the number of available entries between tail and head. This represents the number of
     /// entries that can currently be reserved.
     #[must_use]
     pub fn available(&self) -> u32 {
-        self.tail
+        self.core.available()
+    }
+
+    /// Get the capacity of the ring buffer. This is an alias of [`Self::size`].
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.size()
+    }
+
+    /// Get the number of occupied entries in the ring buffer. These are entries which have been
+    /// committed by the producing side, but not yet committed by the consuming side.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.available() as usize
+    }
+
+    /// Determines if the ring buffer has no occupied entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of entries which have been reserved, but not yet committed.
+    ///
+    /// A large gap which persists indicates a thread reserved entries and then stalled before
+    /// committing them, holding up every entry reserved after it.
+    #[must_use]
+    pub fn pending_commits(&self) -> u32 {
+        self.uncommitted_head
             .load(Ordering::Acquire)
-            .wrapping_sub(self.head.load(Ordering::Acquire))
+            .wrapping_sub(self.core.head.load(Ordering::Acquire))
+    }
+
+    /// Get the entire backing slice of entries, for debugging and inspection.
+    ///
+    /// # Caveats
+    /// This exposes every slot, whether it is free, reserved, or committed, and says nothing
+    /// about which is which. While another thread, the producer side, or the kernel may be using
+    /// the ring, slots can change at any moment, so the contents are only meaningful when the
+    /// caller knows nothing else is accessing the ring. For rings created with `new_big`, each
+    /// entry spans two slots.
+    #[must_use]
+    pub fn entries(&self) -> &'ring [T] {
+        self.core.entries
     }
 
-    /// Determines if the ring buffer is empty, or has no more elements to reserve.
+    /// Determines if the ring buffer has space for another entry, as fewer than [`Self::size`]
+    /// entries are occupied.
     ///
-    /// If this is true, the consuming side of the ring buffer must consume entries to free up
-    /// space. If this returns false, a [`Self::reserve`] operation is only guaranteed to succeed
-    /// if there is only one thread producing on this ring buffer.
+    /// If this returns false, the ring is full, and the producing side can't add entries until
+    /// this side commits some.
+    #[must_use]
+    pub fn has_space(&self) -> bool {
+        (self.available() as usize) < self.size()
+    }
+
+    /// Determines if the ring buffer has space for another entry. Despite its name, this returns
+    /// true while the ring is not full, rather than when it is empty.
+    #[deprecated(note = "use `has_space`, or `is_empty` to check for no occupied entries")]
     #[must_use]
     pub fn empty(&self) -> bool {
-        (self.available() as usize) < self.entries.len()
+        self.has_space()
     }
 
     /// Reserves an entry from the head of the ring buffer.
     #[must_use]
     pub fn reserve(&self) -> Option<ReservedEntry<'ring, T>> {
-        let head = self.uncommitted_head.load(Ordering::Acquire);
-        let tail = self.tail.load(Ordering::Acquire);
+        let (uncommitted_head, head) = self.next_head();
+        let tail = self.core.tail.load(Ordering::Acquire);
 
         if head == tail {
             None
         } else {
-            let entry = &self.entries[((head & self.mask) << self.shift) as usize];
+            let entry = self.core.entry(head);
             if self
                 .uncommitted_head
-                .compare_exchange(head, head + 1, Ordering::Release, Ordering::Relaxed)
+                .compare_exchange(
+                    uncommitted_head,
+                    head.wrapping_add(1),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
                 .is_err()
             {
                 None
@@ -131,6 +202,83 @@ impl<'ring, T> RingBufferConsumer<'ring, T> {
         }
     }
 
+    /// Reserves exactly `n` entries from the head of the ring buffer, or none at all.
+    ///
+    /// The entries are claimed with a single compare-and-swap, so a short batch is never
+    /// reserved. This suits waiting for a known number of completions, such as every operation
+    /// in a barrier.
+    ///
+    /// Returns [`Option::None`] if `n` is zero, fewer than `n` entries are available, or another
+    /// thread reserved some of the same entries first.
+    #[must_use]
+    pub fn reserve_exact_n(&self, n: u32) -> Option<ReservedBatch<'ring, T>> {
+        let (uncommitted_head, head) = self.next_head();
+        let tail = self.core.tail.load(Ordering::Acquire);
+
+        if n == 0 || tail.wrapping_sub(head) < n {
+            return None;
+        }
+
+        self.uncommitted_head
+            .compare_exchange(
+                uncommitted_head,
+                head.wrapping_add(n),
+                Ordering::Release,
+                Ordering::Relaxed,
+            )
+            .ok()
+            .map(|_| self.core.batch(head, n))
+    }
+
+    /// Takes the most recently produced entry, from the tail of the ring buffer.
+    ///
+    /// This allows the ring buffer to be used as a stack, such as a free-list, where handing out
+    /// the most recently returned entry keeps it hot in cache. Moving the tail backwards releases
+    /// the entry to the producing side, so `producer` is borrowed mutably for as long as the
+    /// [`BackReservation`] lives: no entry can be reserved through it, and so overwrite the entry,
+    /// until the reservation is dropped. The entry is never committed; dropping the reservation is
+    /// all that is needed to return it.
+    ///
+    /// `producer` must be the only producer over this ring buffer, so threads sharing the stack
+    /// must share the producer behind a lock. Mixing [`Self::reserve`] and `reserve_back` on the
+    /// same ring buffer is unsupported.
+    ///
+    /// Returns [`Option::None`] if there are no entries to take, `producer` has reservations which
+    /// are not yet committed, or another consumer took the same entry first.
+    ///
+    /// # Panics
+    /// Panics if `producer` does not produce onto the same ring buffer as this consumer.
+    #[must_use]
+    pub fn reserve_back<'p>(
+        &self,
+        producer: &'p mut RingBufferProducer<'ring, T>,
+    ) -> Option<BackReservation<'p, 'ring, T>> {
+        assert!(
+            producer.shares_tail(self.core.tail),
+            "reserve_back requires the producer of the same ring buffer"
+        );
+
+        let (_, head) = self.next_head();
+        let tail = self.core.tail.load(Ordering::Acquire);
+
+        if head == tail || producer.pending_commits() != 0 {
+            return None;
+        }
+
+        let index = tail.wrapping_sub(1);
+        self.core
+            .tail
+            .compare_exchange(tail, index, Ordering::AcqRel, Ordering::Relaxed)
+            .ok()?;
+        // the producer reserves from where the tail now is once the reservation is dropped
+        producer.rewind(index);
+
+        Some(BackReservation {
+            entry: self.core.slot(index),
+            _producer: producer,
+        })
+    }
+
     /// Commit the reserved entry.
     ///
     /// Ensures the reserved entry is the next to be committed, then advances the head of the ring,
@@ -143,22 +291,236 @@ impl<'ring, T> RingBufferConsumer<'ring, T> {
     // Taking `entry` by value is intended to ensure access is no longer possible after committing.
     #[allow(clippy::needless_pass_by_value)]
     pub fn commit(&self, entry: ReservedEntry<'ring, T>) -> Result<(), RingBufferError> {
-        if entry.index != self.head.load(Ordering::Acquire) {
+        self.debug_assert_reserved(entry.index, 1);
+
+        if entry.index != self.core.head.load(Ordering::Acquire) {
             return Err(RingBufferError::CommitOutOfOrder);
         }
 
-        self.head.fetch_add(1, Ordering::Release);
+        // `Release`, so the entry is read before the kernel can reuse it; see the module docs
+        self.core.head.fetch_add(1, Ordering::Release);
         Ok(())
     }
+
+    /// Commit the reserved batch.
+    ///
+    /// Ensures the first entry of the batch is next to be committed, then advances the head of
+    /// the ring past every entry in the batch, making space available to the producer.
+    ///
+    /// # Errors
+    /// - If `batch` does not start at the next entry to be committed, because another thread
+    ///   reserved entries before it that have not yet been committed, returns
+    ///   [`RingBufferError::CommitOutOfOrder`].
+    // Taking `batch` by value is intended to ensure access is no longer possible after committing.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn commit_batch(&self, batch: ReservedBatch<'ring, T>) -> Result<(), RingBufferError> {
+        self.debug_assert_reserved(batch.index, batch.len);
+
+        if batch.index != self.core.head.load(Ordering::Acquire) {
+            return Err(RingBufferError::CommitOutOfOrder);
+        }
+
+        // `Release`, so every entry of the batch is read before the kernel can reuse it
+        self.core.head.fetch_add(batch.len, Ordering::Release);
+        Ok(())
+    }
+
+    /// Reserves up to `max` entries, runs `f` on each in order, then commits them.
+    ///
+    /// This keeps the reserve and commit bookkeeping internal for the common case of processing
+    /// each available entry. Stops at the tail of the ring, so fewer than `max` entries are
+    /// processed if fewer are available. If other threads consume from the same ring, committing
+    /// waits until the entries they reserved first have been committed.
+    ///
+    /// The calling thread must not hold an outstanding reservation from this consumer, such as an
+    /// uncommitted [`ReservedEntry`] from [`Self::reserve`]. The entries reserved here come after
+    /// it, so committing them would wait on it forever.
+    ///
+    /// If `f` panics, every entry reserved by this call is still committed as the panic unwinds,
+    /// so the ring does not stall. Entries after the one `f` panicked on are skipped.
+    ///
+    /// Returns the number of entries processed.
+    pub fn consume<F: FnMut(&T)>(&self, max: u32, mut f: F) -> u32 {
+        let (head, count) = loop {
+            let (uncommitted_head, head) = self.next_head();
+            let tail = self.core.tail.load(Ordering::Acquire);
+            let count = tail.wrapping_sub(head).min(max);
+
+            if count == 0 {
+                return 0;
+            }
+
+            if self
+                .uncommitted_head
+                .compare_exchange(
+                    uncommitted_head,
+                    head.wrapping_add(count),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+            {
+                break (head, count);
+            }
+        };
+
+        let guard = ConsumeGuard {
+            head: self.core.head,
+            index: head,
+            count,
+        };
+        for offset in 0..count {
+            let index = head.wrapping_add(offset);
+            f(self.core.slot(index));
+        }
+        drop(guard);
+
+        count
+    }
+
+    /// Looks at the entry at the head of the ring buffer, committing it only if `f` returns true.
+    ///
+    /// This allows reaping only the entries a caller is interested in, leaving the rest for
+    /// another handler. `f` only inspects the entry; once it accepts, the entry is claimed with a
+    /// compare-and-swap, so among concurrent users of this consumer only one takes it. A clone of
+    /// the entry is then made before the head advances, as the producing side may overwrite the
+    /// entry as soon as it is committed.
+    ///
+    /// Returns [`Option::None`] if there are no entries, `f` returned false, an earlier entry is
+    /// reserved but not yet committed, or another thread took the entry first.
+    #[must_use]
+    pub fn peek_commit_if<F: FnOnce(&T) -> bool>(&self, f: F) -> Option<T>
+    where
+        T: Clone,
+    {
+        let (uncommitted_head, head) = self.next_head();
+        let tail = self.core.tail.load(Ordering::Acquire);
+
+        // entries are committed in order, so nothing can be committed past an outstanding
+        // reservation
+        if head == tail || self.core.head.load(Ordering::Acquire) != head {
+            return None;
+        }
+
+        let entry = self.core.slot(head);
+        if !f(entry) {
+            return None;
+        }
+
+        self.uncommitted_head
+            .compare_exchange(
+                uncommitted_head,
+                head.wrapping_add(1),
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            )
+            .ok()?;
+        let entry = entry.clone();
+
+        // the claim only succeeds while nothing is reserved ahead of the entry, and nothing after
+        // it can be committed first, so the head is still at the entry and only this thread can
+        // move it. `Release`, so the clone happens before the kernel can reuse the entry
+        self.core.head.fetch_add(1, Ordering::Release);
+
+        Some(entry)
+    }
+
+    /// Load the uncommitted head, along with the index the next reservation should start from.
+    ///
+    /// The committed head only trails the uncommitted head while entries are reserved. If it is
+    /// ahead instead, it was advanced from outside this consumer, such as by another consumer
+    /// over the same ring. Reservations then start from the committed head, so entries which were
+    /// already consumed are never read again. Moving the head backwards is not supported.
+    fn next_head(&self) -> (u32, u32) {
+        let uncommitted_head = self.uncommitted_head.load(Ordering::Acquire);
+        let head = self.core.head.load(Ordering::Acquire);
+        let ahead = head.wrapping_sub(uncommitted_head);
+
+        if ahead != 0 && ahead < 1 << 31 {
+            (uncommitted_head, head)
+        } else {
+            (uncommitted_head, uncommitted_head)
+        }
+    }
+
+    /// Panics in debug builds if the `len` entries starting at `index` are not outstanding
+    /// reservations of this consumer.
+    ///
+    /// Committing such entries is never valid, whatever order the reservations are committed in,
+    /// so retrying on [`RingBufferError::CommitOutOfOrder`] would never succeed.
+    #[track_caller]
+    fn debug_assert_reserved(&self, index: u32, len: u32) {
+        if cfg!(debug_assertions) {
+            let head = self.core.head.load(Ordering::Acquire);
+            let uncommitted_head = self.uncommitted_head.load(Ordering::Acquire);
+            let reserved = uncommitted_head.wrapping_sub(head);
+
+            assert!(
+                len <= reserved && index.wrapping_sub(head) <= reserved - len,
+                "impossible commit order: expected entries reserved in {head}..{uncommitted_head}, \
+                 got {index}..{}",
+                index.wrapping_add(len),
+            );
+        }
+    }
+}
+
+/// An entry taken from the tail of the ring buffer by [`RingBufferConsumer::reserve_back`].
+///
+/// The entry has already been released to the producing side, which is kept from reserving it
+/// again by the mutable borrow of the producer held here. Dropping the reservation ends that
+/// borrow, after which the entry may be overwritten.
+#[derive(Debug)]
+pub struct BackReservation<'p, 'ring, T> {
+    entry: &'ring T,
+    _producer: &'p mut RingBufferProducer<'ring, T>,
+}
+
+impl<T> Deref for BackReservation<'_, '_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        self.entry
+    }
+}
+
+/// Commits the entries reserved by [`RingBufferConsumer::consume`] when dropped, so they are
+/// committed even if processing them panics.
+struct ConsumeGuard<'ring> {
+    head: &'ring AtomicU32,
+    index: u32,
+    count: u32,
+}
+
+impl Drop for ConsumeGuard<'_> {
+    fn drop(&mut self) {
+        // `Release` on success, so the reads of the entries happen before the kernel can reuse
+        // them
+        while self
+            .head
+            .compare_exchange_weak(
+                self.index,
+                self.index.wrapping_add(self.count),
+                Ordering::Release,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            hint::spin_loop();
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::panic::AssertUnwindSafe;
+
+    use loom::cell::UnsafeCell;
     use loom::thread::{self, yield_now};
 
-    use crate::sync::Arc;
     use crate::sync::atomic::{AtomicU32, Ordering};
-    use crate::{RingBufferConsumer, RingBufferError};
+    use crate::sync::{Arc, Mutex};
+    use crate::{RingBufferConsumer, RingBufferError, RingBufferProducer};
 
     #[test]
     fn new_returns_err_when_entries_is_larger_than_u32() {
@@ -202,6 +564,34 @@ mod test {
         });
     }
 
+    #[test]
+    fn new_returns_err_when_entries_is_empty() {
+        loom::model(|| {
+            let entries: Vec<u32> = vec![];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 0;
+
+            let result = RingBufferConsumer::new(&entries, &head, &tail, mask);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::EmptyRing));
+        });
+    }
+
+    #[test]
+    fn new_big_returns_err_when_entries_has_one_element() {
+        loom::model(|| {
+            let entries = vec![0u32; 1];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 0;
+
+            let result = RingBufferConsumer::new_big(&entries, &head, &tail, mask);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::EmptyRing));
+        });
+    }
+
     #[test]
     fn new_size_returns_entries_len() {
         loom::model(|| {
@@ -274,6 +664,77 @@ mod test {
         });
     }
 
+    #[test]
+    fn capacity_returns_size() {
+        loom::model(|| {
+            let entries = vec![0u32; 32];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 32 - 1;
+            let consumer = RingBufferConsumer::new_big(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(consumer.capacity(), consumer.size());
+        });
+    }
+
+    #[test]
+    fn new_big_checked_takes_mask_over_large_objects() {
+        loom::model(|| {
+            let entries = vec![0u32; 32];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 16 - 1;
+
+            let consumer =
+                RingBufferConsumer::new_big_checked(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(consumer.size(), 16);
+        });
+    }
+
+    #[test]
+    fn new_big_checked_rejects_mismatched_mask() {
+        loom::model(|| {
+            let entries = vec![0u32; 32];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+
+            // a mask over every entry, as `new_big` takes, describes twice the capacity
+            for mask in [32 - 1, 8 - 1, (1 << 31) | (16 - 1), u32::MAX] {
+                let result = RingBufferConsumer::new_big_checked(&entries, &head, &tail, mask);
+
+                assert!(result.is_err_and(|e| e == RingBufferError::InvalidMaskValue));
+            }
+        });
+    }
+
+    #[test]
+    fn len_tracks_reserve_and_commit() {
+        loom::model(|| {
+            const ENTRIES: usize = 2;
+            let entries = vec![0u32; ENTRIES];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(u32::try_from(ENTRIES).unwrap());
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(consumer.len(), consumer.size());
+            assert!(!consumer.is_empty());
+            assert!(!consumer.has_space());
+
+            let entry = consumer.reserve().unwrap();
+            assert_eq!(consumer.len(), consumer.size());
+            consumer.commit(entry).unwrap();
+            assert_eq!(consumer.len(), 1);
+            assert!(consumer.has_space());
+
+            let entry = consumer.reserve().unwrap();
+            consumer.commit(entry).unwrap();
+            assert_eq!(consumer.len(), 0);
+            assert!(consumer.is_empty());
+        });
+    }
+
     #[test]
     fn reserves_no_entries_when_none_are_available() {
         loom::model(|| {
@@ -306,6 +767,40 @@ mod test {
         });
     }
 
+    #[test]
+    fn pending_commits_counts_uncommitted_reservations() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(4);
+            let mask = 4 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let first = consumer.reserve().unwrap();
+            let _second = consumer.reserve().unwrap();
+            let _third = consumer.reserve().unwrap();
+            assert_eq!(consumer.pending_commits(), 3);
+
+            consumer.commit(first).unwrap();
+            assert_eq!(consumer.pending_commits(), 2);
+        });
+    }
+
+    #[test]
+    fn entries_exposes_the_backing_slice() {
+        loom::model(|| {
+            let entries = vec![5u32, 6, 7, 8];
+            let head = AtomicU32::new(2);
+            let tail = AtomicU32::new(4);
+            let mask = 4 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let pending = &consumer.entries()[2..4];
+            assert_eq!(pending, [7, 8]);
+            assert_eq!(*consumer.reserve().unwrap(), pending[0]);
+        });
+    }
+
     #[test]
     fn committing_entries_out_of_order_returns_error() {
         loom::model(|| {
@@ -325,6 +820,368 @@ mod test {
         });
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "impossible commit order")]
+    fn committing_entry_from_another_ring_panics_in_debug() {
+        loom::model(|| {
+            let entries = vec![0u32; 2];
+            let head = AtomicU32::new(0);
+            let other_head = AtomicU32::new(0);
+            let tail = AtomicU32::new(2);
+            let mask = 2 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+            let other = RingBufferConsumer::new(&entries, &other_head, &tail, mask).unwrap();
+
+            let entry = other.reserve().unwrap();
+
+            // the index matches the head, so without the assertion this would be accepted
+            let _ = consumer.commit(entry);
+        });
+    }
+
+    #[test]
+    fn consume_processes_up_to_max_entries() {
+        loom::model(|| {
+            let entries = vec![0u32, 1, 2, 3, 4, 5, 6, 7];
+            // start near the end of the ring, so the consumed entries wrap
+            let head = AtomicU32::new(6);
+            let tail = AtomicU32::new(11);
+            let mask = 8 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let mut seen = Vec::new();
+            let processed = consumer.consume(3, |entry| seen.push(*entry));
+
+            assert_eq!(processed, 3);
+            assert_eq!(seen, [6, 7, 0]);
+            assert_eq!(head.load(Ordering::Acquire), 9);
+            assert_eq!(consumer.len(), 2);
+        });
+    }
+
+    #[test]
+    fn consume_commits_entries_when_f_panics() {
+        loom::model(|| {
+            let entries = vec![0u32, 1, 2, 3];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(4);
+            let mask = 4 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                consumer.consume(3, |entry| assert_ne!(*entry, 1));
+            }));
+            assert!(result.is_err());
+
+            // the entries reserved by the panicking call were released, rather than stalling
+            assert_eq!(head.load(Ordering::Acquire), 3);
+            let mut seen = Vec::new();
+            assert_eq!(consumer.consume(4, |entry| seen.push(*entry)), 1);
+            assert_eq!(seen, [3]);
+        });
+    }
+
+    #[test]
+    fn consume_stops_at_tail() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(2);
+            let mask = 4 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(consumer.consume(4, |_| {}), 2);
+            assert_eq!(consumer.consume(4, |_| {}), 0);
+            assert_eq!(head.load(Ordering::Acquire), 2);
+        });
+    }
+
+    #[test]
+    fn reaps_batches_over_time_without_skipping_or_repeating() {
+        loom::model(|| {
+            const ENTRIES: usize = 4;
+            let entries = Arc::new(vec![0u32, 1, 2, 3]);
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let head = Arc::new(AtomicU32::new(0));
+            let tail = Arc::new(AtomicU32::new(0));
+
+            // the kernel completes entries in two batches
+            let kernel = {
+                let tail = tail.clone();
+                thread::spawn(move || {
+                    tail.fetch_add(2, Ordering::Release);
+                    tail.fetch_add(2, Ordering::Release);
+                })
+            };
+
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+            let mut seen = Vec::new();
+            while seen.len() < ENTRIES {
+                if consumer.consume(u32::MAX, |entry| seen.push(*entry)) == 0 {
+                    yield_now();
+                }
+            }
+            kernel.join().unwrap();
+
+            assert_eq!(seen, [0, 1, 2, 3]);
+            assert_eq!(head.load(Ordering::Acquire), 4);
+        });
+    }
+
+    #[test]
+    fn catches_up_with_head_advanced_elsewhere() {
+        loom::model(|| {
+            let entries = vec![0u32, 1, 2, 3];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(4);
+            let mask = 4 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let entry = consumer.reserve().unwrap();
+            assert_eq!(*entry, 0);
+            consumer.commit(entry).unwrap();
+
+            // another consumer over the same ring reaps the next two entries
+            let other = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+            assert_eq!(other.consume(2, |_| {}), 2);
+
+            let entry = consumer.reserve().unwrap();
+            assert_eq!(*entry, 3);
+            consumer.commit(entry).unwrap();
+            assert!(consumer.reserve().is_none());
+            assert_eq!(head.load(Ordering::Acquire), 4);
+        });
+    }
+
+    #[test]
+    fn peek_commit_if_leaves_rejected_entries() {
+        loom::model(|| {
+            let entries = vec![1u32, 2];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(2);
+            let mask = 2 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            assert!(consumer.peek_commit_if(|entry| *entry == 2).is_none());
+            assert_eq!(head.load(Ordering::Acquire), 0);
+
+            assert_eq!(consumer.peek_commit_if(|entry| *entry == 1), Some(1));
+            assert_eq!(consumer.peek_commit_if(|entry| *entry == 2), Some(2));
+            assert!(consumer.peek_commit_if(|_| true).is_none());
+            assert_eq!(head.load(Ordering::Acquire), 2);
+            assert_eq!(consumer.pending_commits(), 0);
+        });
+    }
+
+    #[test]
+    fn peek_commit_if_splits_entries_between_consumers() {
+        loom::model(|| {
+            const ENTRIES: usize = 2;
+
+            // consumers share a single `RingBufferConsumer`, which must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![1u32, 2].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(2)));
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let consumer = Arc::new(RingBufferConsumer::new(entries, head, tail, mask).unwrap());
+
+            let take = |consumer: &RingBufferConsumer<'static, u32>, odd: bool| loop {
+                if let Some(entry) = consumer.peek_commit_if(|entry| (entry % 2 == 1) == odd) {
+                    return entry;
+                }
+
+                yield_now();
+            };
+
+            let even = {
+                let consumer = consumer.clone();
+                thread::spawn(move || take(&consumer, false))
+            };
+            let odd = take(&consumer, true);
+            let even = even.join().unwrap();
+
+            assert_eq!((odd, even), (1, 2));
+            assert_eq!(head.load(Ordering::Acquire), 2);
+        });
+    }
+
+    #[test]
+    fn reserve_exact_n_reserves_all_or_nothing() {
+        loom::model(|| {
+            let entries = vec![0u32, 1, 2, 3];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(3);
+            let mask = 4 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            assert!(consumer.reserve_exact_n(4).is_none());
+            assert!(consumer.reserve_exact_n(0).is_none());
+            assert_eq!(consumer.pending_commits(), 0);
+
+            let batch = consumer.reserve_exact_n(3).unwrap();
+            assert_eq!(batch.iter().copied().collect::<Vec<_>>(), [0, 1, 2]);
+            consumer.commit_batch(batch).unwrap();
+            assert_eq!(head.load(Ordering::Acquire), 3);
+        });
+    }
+
+    #[test]
+    fn reserve_exact_n_never_reserves_short_or_shared_batches() {
+        loom::model(|| {
+            const ENTRIES: usize = 4;
+
+            // consumers share a single `RingBufferConsumer`, which must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![0u32, 1, 2, 3].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(4)));
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let consumer = Arc::new(RingBufferConsumer::new(entries, head, tail, mask).unwrap());
+
+            let reserve = |consumer: &RingBufferConsumer<'static, u32>| loop {
+                if let Some(batch) = consumer.reserve_exact_n(2) {
+                    return batch;
+                }
+
+                yield_now();
+            };
+
+            let other = {
+                let consumer = consumer.clone();
+                thread::spawn(move || reserve(&consumer))
+            };
+            let this = reserve(&consumer);
+            let other = other.join().unwrap();
+
+            assert_eq!((this.len(), other.len()), (2, 2));
+            let (first, second) = if this.index < other.index {
+                (this, other)
+            } else {
+                (other, this)
+            };
+            assert_eq!(
+                first.iter().chain(&second).copied().collect::<Vec<_>>(),
+                [0, 1, 2, 3]
+            );
+
+            consumer.commit_batch(first).unwrap();
+            consumer.commit_batch(second).unwrap();
+            assert_eq!(head.load(Ordering::Acquire), 4);
+        });
+    }
+
+    #[test]
+    fn reserve_back_reserves_most_recent_entries_first() {
+        loom::model(|| {
+            let entries = vec![0u32, 1, 2, 3];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(3);
+            let mask = 4 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+            let mut producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            for expected in [2, 1, 0] {
+                assert_eq!(*consumer.reserve_back(&mut producer).unwrap(), expected);
+            }
+
+            assert!(consumer.reserve_back(&mut producer).is_none());
+            assert_eq!(head.load(Ordering::Acquire), 0);
+            assert_eq!(tail.load(Ordering::Acquire), 0);
+        });
+    }
+
+    #[test]
+    fn reserve_back_returns_entry_to_producer() {
+        loom::model(|| {
+            let entries = vec![0u32, 1];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(2);
+            let mask = 2 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+            let mut producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(*consumer.reserve_back(&mut producer).unwrap(), 1);
+
+            let entry = producer.reserve().unwrap();
+            assert!(consumer.reserve_back(&mut producer).is_none());
+            producer.commit(entry).unwrap();
+
+            assert_eq!(tail.load(Ordering::Acquire), 2);
+            assert_eq!(*consumer.reserve_back(&mut producer).unwrap(), 1);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "reserve_back requires the producer of the same ring buffer")]
+    fn reserve_back_rejects_producer_of_another_ring() {
+        loom::model(|| {
+            let entries = vec![0u32; 2];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(1);
+            let other_tail = AtomicU32::new(1);
+            let mask = 2 - 1;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+            let mut producer = RingBufferProducer::new(&entries, &head, &other_tail, mask).unwrap();
+
+            let _ = consumer.reserve_back(&mut producer);
+        });
+    }
+
+    #[test]
+    fn reserve_back_does_not_reserve_entries_twice() {
+        loom::model(|| {
+            // consumers share a single `RingBufferConsumer`, and the producer behind a lock, which
+            // must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![0u32, 1].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(2)));
+            let mask = 2 - 1;
+            let consumer = Arc::new(RingBufferConsumer::new(entries, head, tail, mask).unwrap());
+            let producer = Arc::new(Mutex::new(
+                RingBufferProducer::new(entries, head, tail, mask).unwrap(),
+            ));
+
+            let take = |consumer: &RingBufferConsumer<'static, u32>,
+                        producer: &Mutex<RingBufferProducer<'static, u32>>| {
+                let mut producer = producer.lock().unwrap();
+                let entry = consumer.reserve_back(&mut producer).unwrap();
+                *entry
+            };
+
+            let other = {
+                let consumer = consumer.clone();
+                let producer = producer.clone();
+                thread::spawn(move || take(&consumer, &producer))
+            };
+            let this = take(&consumer, &producer);
+            let other = other.join().unwrap();
+
+            assert_ne!(this, other);
+            assert_eq!(tail.load(Ordering::Acquire), 0);
+        });
+    }
+
+    #[test]
+    fn single_entry_ring_holds_one_entry_at_a_time() {
+        loom::model(|| {
+            let entries = vec![0u32; 1];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(1);
+            let mask = 0;
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let entry = consumer.reserve().unwrap();
+            assert!(consumer.reserve().is_none());
+            consumer.commit(entry).unwrap();
+            assert!(consumer.reserve().is_none());
+
+            tail.fetch_add(1, Ordering::Release);
+            let entry = consumer.reserve().unwrap();
+            consumer.commit(entry).unwrap();
+            assert_eq!(head.load(Ordering::Acquire), 2);
+        });
+    }
+
     #[test]
     fn reserves_entry_when_some_are_available() {
         loom::model(|| {
@@ -355,6 +1212,85 @@ mod test {
             });
         });
     }
+
+    // a CQE the kernel writes into, which loom checks is never accessed concurrently
+    struct Cqe(UnsafeCell<u32>);
+
+    // SAFETY: access to the entry is synchronized through the head and tail of the ring.
+    unsafe impl Sync for Cqe {}
+
+    impl Clone for Cqe {
+        fn clone(&self) -> Self {
+            Self(UnsafeCell::new(read(self)))
+        }
+    }
+
+    fn read(cqe: &Cqe) -> u32 {
+        // SAFETY: the entry is reserved, so the kernel cannot write it until the head moves.
+        cqe.0.with(|value| unsafe { *value })
+    }
+
+    /// Model the kernel waiting for the head to move past the only CQE before overwriting it,
+    /// while `advance` reads the CQE and moves the head. Unless the head advance releases the
+    /// read, loom reports the read and overwrite as a data race.
+    fn kernel_overwrites_after_head_advances(
+        advance: fn(&RingBufferConsumer<'static, Cqe>) -> u32,
+    ) {
+        loom::model(move || {
+            let entries: &'static [Cqe] =
+                Box::leak(vec![Cqe(UnsafeCell::new(7))].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(1)));
+            let consumer = RingBufferConsumer::new(entries, head, tail, 0).unwrap();
+
+            let kernel = thread::spawn(move || {
+                while head.load(Ordering::Acquire) == 0 {
+                    yield_now();
+                }
+                // SAFETY: the head has moved past the entry, releasing it to the kernel.
+                entries[0].0.with_mut(|value| unsafe { *value = 8 });
+            });
+
+            assert_eq!(advance(&consumer), 7);
+            kernel.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn commit_releases_entry_to_kernel() {
+        kernel_overwrites_after_head_advances(|consumer| {
+            let entry = consumer.reserve().unwrap();
+            let value = read(&entry);
+            consumer.commit(entry).unwrap();
+            value
+        });
+    }
+
+    #[test]
+    fn commit_batch_releases_entries_to_kernel() {
+        kernel_overwrites_after_head_advances(|consumer| {
+            let batch = consumer.reserve_exact_n(1).unwrap();
+            let value = read(batch.get(0).unwrap());
+            consumer.commit_batch(batch).unwrap();
+            value
+        });
+    }
+
+    #[test]
+    fn consume_releases_entries_to_kernel() {
+        kernel_overwrites_after_head_advances(|consumer| {
+            let mut value = 0;
+            assert_eq!(consumer.consume(1, |cqe| value = read(cqe)), 1);
+            value
+        });
+    }
+
+    #[test]
+    fn peek_commit_if_releases_entry_to_kernel() {
+        kernel_overwrites_after_head_advances(|consumer| {
+            read(&consumer.peek_commit_if(|_| true).unwrap())
+        });
+    }
 }
 
 #[cfg(feature = "internal_benches")]
@@ -373,12 +1309,13 @@ mod benches {
         let mask = u32::try_from(N).unwrap() - 1;
         let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
 
+        // every iteration fills the ring as the producer would, then drains it, so each
+        // iteration measures the same full cycle however many have run before
         bencher.counter(ItemsCount::new(N)).bench(|| {
             tail.fetch_add(u32::try_from(N).unwrap(), Ordering::Release);
             for _ in 0..N {
-                if let Some(item) = consumer.reserve() {
-                    let _ = consumer.commit(item);
-                }
+                let item = consumer.reserve().unwrap();
+                consumer.commit(item).unwrap();
             }
         });
     }
@@ -1,15 +1,29 @@
 //! A generic ring buffer
 
+pub mod backoff;
+pub use backoff::*;
+
 pub mod producer;
 pub use producer::*;
 
 pub mod consumer;
 pub use consumer::*;
 
+pub mod owned;
+pub use owned::*;
+
+pub mod static_ring;
+pub use static_ring::*;
+
+mod base;
+mod storage;
+
+use std::cell::UnsafeCell;
 use std::{fmt::Display, ops::Deref};
 
 /// Errors that occur as a result of using [`RingBufferConsumer`]
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
 pub enum RingBufferError {
     #[default]
     /// There are too many entries in the slice.
@@ -21,50 +35,467 @@ pub enum RingBufferError {
     /// A commit was attempted out of order. Another thread may have the next entry to commit.
     /// Retrying the operation may succeed.
     CommitOutOfOrder,
+    /// The ring buffer has no space for another entry.
+    Full,
+    /// Another thread reserved the same entry first. Retrying the operation may succeed.
+    Contended,
+    /// The ring buffer would have no room for any entries.
+    EmptyRing,
+    /// The requested alignment was not a power of two.
+    InvalidAlignment,
+    /// The ring buffer still holds entries.
+    NotEmpty,
 }
 
-impl Display for RingBufferError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl RingBufferError {
+    /// Get a description of the error, without going through the formatting machinery.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
         match self {
-            Self::EntriesSliceTooLong => f.write_str("Entries slice was too long for the ring buffer."),
-            Self::LengthNotPowerOfTwo => f.write_str("Length of entries was not a power of two."),
-            Self::InvalidMaskValue => {
-                f.write_str("Mask has incorrect value for length of entries.")
+            Self::EntriesSliceTooLong => "Entries slice was too long for the ring buffer.",
+            Self::LengthNotPowerOfTwo => "Length of entries was not a power of two.",
+            Self::InvalidMaskValue => "Mask has incorrect value for length of entries.",
+            Self::CommitOutOfOrder => {
+                "A commit was attempted out of order. Another thread may have the next entry to commit. Retrying the operation may succeed."
+            }
+            Self::Full => "The ring buffer has no space for another entry.",
+            Self::Contended => {
+                "Another thread reserved the same entry first. Retrying the operation may succeed."
             }
-            Self::CommitOutOfOrder => f.write_str("A commit was attempted out of order. Another thread may have the next entry to commit. Retrying the operation may succeed.")
+            Self::EmptyRing => "The ring buffer would have no room for any entries.",
+            Self::InvalidAlignment => "The requested alignment was not a power of two.",
+            Self::NotEmpty => "The ring buffer still holds entries.",
         }
     }
 }
 
+impl Display for RingBufferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Helpers shared by every kind of ring buffer, such as for setting up a [`RingBufferProducer`]
+/// and [`RingBufferConsumer`] pair over existing entries.
+///
+/// This is never constructed, and only groups associated functions.
+#[derive(Debug, Clone, Copy)]
+pub enum RingBuffer {}
+
+impl RingBuffer {
+    /// Computes the mask for a ring buffer over `len` entries, as expected by
+    /// [`RingBufferProducer::new`] and [`RingBufferConsumer::new`].
+    ///
+    /// ```
+    /// # use fern_uring::{RingBuffer, RingBufferProducer};
+    /// # use std::sync::atomic::AtomicU32;
+    /// let entries = [0u32; 8];
+    /// let (head, tail) = (AtomicU32::new(0), AtomicU32::new(0));
+    ///
+    /// let mask = RingBuffer::mask_for(entries.len())?;
+    /// let producer = RingBufferProducer::new(&entries, &head, &tail, mask)?;
+    /// # Ok::<(), fern_uring::RingBufferError>(())
+    /// ```
+    ///
+    /// # Errors
+    /// - if `len` is greater than `u32::MAX`, the [`RingBufferError::EntriesSliceTooLong`] error
+    ///   is returned.
+    /// - if `len` is zero, the [`RingBufferError::EmptyRing`] error is returned.
+    /// - if `len` is not a power of two, the [`RingBufferError::LengthNotPowerOfTwo`] error is
+    ///   returned.
+    pub fn mask_for(len: usize) -> Result<u32, RingBufferError> {
+        let len = u32::try_from(len).map_err(|_| RingBufferError::EntriesSliceTooLong)?;
+        if len == 0 {
+            return Err(RingBufferError::EmptyRing);
+        }
+        if !len.is_power_of_two() {
+            return Err(RingBufferError::LengthNotPowerOfTwo);
+        }
+
+        Ok(len - 1)
+    }
+}
+
+/// Get the first physical slot of the entry at `index`, in a ring of `len` slots where `mask`
+/// covers entries and each entry spans `1 << shift` slots.
+fn entry_start(index: u32, mask: u32, shift: u32, len: usize) -> usize {
+    let start = ((index & mask) << shift) as usize;
+    debug_assert!(
+        start + (1 << shift) <= len,
+        "entry {index} spans slots {start}..{}, but the ring only has {len}",
+        start + (1 << shift)
+    );
+
+    start
+}
+
 /// An entry returned as part of a reserve operation.
+///
+/// # Thread safety
+/// A `ReservedEntry` only holds a shared reference to its slot, so it is [`Send`] and [`Sync`]
+/// exactly when `T` is [`Sync`]. Moving a reservation to another thread is sound: the reservation
+/// claims an index in the ring, not a thread, and [`RingBufferProducer::commit`] and
+/// [`RingBufferConsumer::commit`] validate that index against the shared head or tail regardless
+/// of which thread commits it. Mutation of the slot can only happen through interior mutability
+/// in `T`, which is already bounded by `T`'s own [`Sync`] implementation.
 #[derive(Debug)]
 pub struct ReservedEntry<'ring, T> {
     index: u32,
-    entry: &'ring T,
+    // every physical slot making up the entry; two for rings created with `new_big`
+    entry: &'ring [T],
 }
 
 impl<'ring, T> ReservedEntry<'ring, T> {
-    fn new(index: u32, entry: &'ring T) -> Self {
+    fn new(index: u32, entry: &'ring [T]) -> Self {
         Self { index, entry }
     }
+
+    /// Get both physical slots of an entry reserved from a ring created with `new_big`.
+    ///
+    /// Big entries, such as 128-byte SQEs or 32-byte CQEs, span two contiguous slots of the
+    /// underlying entries. Dereferencing only yields the first. Returns [`Option::None`] if the
+    /// ring was not created with `new_big`.
+    #[must_use]
+    pub fn as_pair(&self) -> Option<&[T; 2]> {
+        self.entry.try_into().ok()
+    }
+
+    /// Get a raw pointer to the reserved slot, such as for handing to C helpers which build an
+    /// entry in place.
+    ///
+    /// The pointer stays valid for as long as the ring's entries, but the slot is only reserved
+    /// for the holder of this entry until it is committed. Once committed, the other side of the
+    /// ring (possibly the kernel) may read it as soon as the tail advances, and it may be reserved
+    /// and overwritten again once consumed. For rings created with `new_big`, the second slot
+    /// directly follows the first.
+    ///
+    /// The pointer is derived from a shared reference, so writing through it is only sound when
+    /// the write goes through interior mutability in `T`, such as an [`UnsafeCell`]. For
+    /// entries held in an [`UnsafeCell`], [`Self::as_mut_ptr`] gives a pointer to write through
+    /// directly.
+    #[must_use]
+    pub fn as_ptr(&self) -> *const T {
+        self.entry.as_ptr()
+    }
+}
+
+impl<T> ReservedEntry<'_, UnsafeCell<T>> {
+    /// Get a raw pointer to the value in the reserved slot, which may be written through, such as
+    /// by C helpers which build an entry in place.
+    ///
+    /// The pointer is valid for the same window as [`Self::as_ptr`]: writes must be finished
+    /// before the entry is committed. For rings created with `new_big`, the value in the second
+    /// slot directly follows the first. Entries in an [`UnsafeCell`] are not [`Sync`], so the
+    /// reservation, and any writes through the pointer, stay on the thread which reserved it.
+    #[must_use]
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        // derived from the whole entry, so the pointer also covers the second slot of big entries
+        UnsafeCell::raw_get(self.entry.as_ptr())
+    }
 }
 
 impl<T> Deref for ReservedEntry<'_, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        self.entry
+        &self.entry[0]
+    }
+}
+
+/// A contiguous run of entries returned as part of a batch reserve operation.
+///
+/// Like [`ReservedEntry`], a `ReservedBatch` is [`Send`] and [`Sync`] exactly when `T` is [`Sync`].
+#[derive(Debug)]
+pub struct ReservedBatch<'ring, T> {
+    index: u32,
+    len: u32,
+    entries: &'ring [T],
+    mask: u32,
+    shift: u32,
+}
+
+impl<'ring, T> ReservedBatch<'ring, T> {
+    fn new(index: u32, len: u32, entries: &'ring [T], mask: u32, shift: u32) -> Self {
+        Self {
+            index,
+            len,
+            entries,
+            mask,
+            shift,
+        }
+    }
+
+    /// Get the number of entries in the batch.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Determines if the batch contains no entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the entry at `offset` within the batch, or [`Option::None`] if `offset` is out of
+    /// bounds. Entries are ordered as they are in the ring, accounting for wrap around.
+    #[must_use]
+    pub fn get(&self, offset: usize) -> Option<&'ring T> {
+        let offset = u32::try_from(offset)
+            .ok()
+            .filter(|offset| *offset < self.len)?;
+        let index = self.index.wrapping_add(offset);
+
+        Some(&self.entries[entry_start(index, self.mask, self.shift, self.entries.len())])
+    }
+
+    /// Iterate over the entries in the batch, in ring order.
+    #[must_use]
+    pub fn iter(&self) -> ReservedBatchIter<'_, 'ring, T> {
+        ReservedBatchIter {
+            batch: self,
+            offset: 0,
+        }
     }
 }
 
+impl<'batch, 'ring, T> IntoIterator for &'batch ReservedBatch<'ring, T> {
+    type Item = &'ring T;
+    type IntoIter = ReservedBatchIter<'batch, 'ring, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a [`ReservedBatch`].
+///
+/// Iteration borrows the batch rather than consuming it, so the batch can still be committed
+/// afterwards.
+#[derive(Debug)]
+pub struct ReservedBatchIter<'batch, 'ring, T> {
+    batch: &'batch ReservedBatch<'ring, T>,
+    offset: usize,
+}
+
+impl<'ring, T> Iterator for ReservedBatchIter<'_, 'ring, T> {
+    type Item = &'ring T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.batch.get(self.offset)?;
+        self.offset += 1;
+
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.batch.len() - self.offset;
+
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for ReservedBatchIter<'_, '_, T> {}
+
 #[cfg(test)]
 mod test {
+    use std::cell::UnsafeCell;
+
     use loom::thread::{self, yield_now};
 
     use crate::sync::Arc;
     use crate::sync::atomic::{AtomicU32, Ordering};
     use crate::{RingBufferConsumer, RingBufferProducer};
 
+    use super::{ReservedBatch, ReservedEntry, RingBuffer, RingBufferError};
+
+    #[test]
+    fn error_as_str_matches_display() {
+        let errors = [
+            RingBufferError::EntriesSliceTooLong,
+            RingBufferError::LengthNotPowerOfTwo,
+            RingBufferError::InvalidMaskValue,
+            RingBufferError::CommitOutOfOrder,
+            RingBufferError::Full,
+            RingBufferError::Contended,
+            RingBufferError::EmptyRing,
+            RingBufferError::InvalidAlignment,
+            RingBufferError::NotEmpty,
+        ];
+
+        for error in errors {
+            assert_eq!(error.as_str(), error.to_string());
+        }
+    }
+
+    #[test]
+    fn mask_for_returns_len_minus_one_for_powers_of_two() {
+        for shift in 0..32 {
+            let len = 1usize << shift;
+            assert_eq!(
+                RingBuffer::mask_for(len),
+                Ok(u32::try_from(len - 1).unwrap())
+            );
+        }
+    }
+
+    #[test]
+    fn mask_for_rejects_invalid_lengths() {
+        assert_eq!(RingBuffer::mask_for(0), Err(RingBufferError::EmptyRing));
+        for len in [3, 6, 31, 33, 1000] {
+            assert_eq!(
+                RingBuffer::mask_for(len),
+                Err(RingBufferError::LengthNotPowerOfTwo)
+            );
+        }
+        assert_eq!(
+            RingBuffer::mask_for(1 << 32),
+            Err(RingBufferError::EntriesSliceTooLong)
+        );
+    }
+
+    #[test]
+    fn reservations_are_send_and_sync_when_entries_are_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<ReservedEntry<'_, u32>>();
+        assert_send_sync::<ReservedBatch<'_, u32>>();
+    }
+
+    #[test]
+    fn big_rings_hold_half_as_many_entries_across_the_wrap() {
+        loom::model(|| {
+            let entries: Vec<_> = (0..8).map(|_| AtomicU32::new(0)).collect();
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer =
+                RingBufferProducer::new_big_checked(&entries, &head, &tail, mask).unwrap();
+            let consumer =
+                RingBufferConsumer::new_big_checked(&entries, &head, &tail, mask).unwrap();
+
+            for round in 0..2 {
+                for value in 0..4 {
+                    let entry = producer.reserve().unwrap();
+                    let [first, second] = entry.as_pair().unwrap();
+                    first.store(round * 8 + value * 2, Ordering::Relaxed);
+                    second.store(round * 8 + value * 2 + 1, Ordering::Relaxed);
+                    producer.commit(entry).unwrap();
+                }
+                assert!(
+                    producer
+                        .try_reserve()
+                        .is_err_and(|e| e == RingBufferError::Full)
+                );
+
+                let batch = consumer.reserve_exact_n(4).unwrap();
+                let firsts: Vec<_> = batch.iter().map(|e| e.load(Ordering::Relaxed)).collect();
+                assert_eq!(firsts, [0, 2, 4, 6].map(|value| round * 8 + value));
+                consumer.commit_batch(batch).unwrap();
+            }
+        });
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "but the ring only has 4")]
+    fn entry_start_asserts_entries_are_in_bounds() {
+        // a mask over every entry, used as though it covered large objects
+        let _ = super::entry_start(2, 4 - 1, 1, 4);
+    }
+
+    #[test]
+    fn big_entries_expose_both_slots() {
+        loom::model(|| {
+            let entries: Vec<_> = (0..4).map(|_| AtomicU32::new(0)).collect();
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new_big(&entries, &head, &tail, mask).unwrap();
+            let consumer = RingBufferConsumer::new_big(&entries, &head, &tail, mask).unwrap();
+
+            for value in [1, 3] {
+                let entry = producer.reserve().unwrap();
+                let [first, second] = entry.as_pair().unwrap();
+                first.store(value, Ordering::Relaxed);
+                second.store(value + 1, Ordering::Relaxed);
+                producer.commit(entry).unwrap();
+            }
+
+            for value in [1, 3] {
+                let entry = consumer.reserve().unwrap();
+                let [first, second] = entry.as_pair().unwrap();
+                assert_eq!(first.load(Ordering::Relaxed), value);
+                assert_eq!(second.load(Ordering::Relaxed), value + 1);
+                consumer.commit(entry).unwrap();
+            }
+        });
+    }
+
+    #[test]
+    fn entries_are_not_pairs_unless_big() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let entry = producer.reserve().unwrap();
+
+            assert!(entry.as_pair().is_none());
+        });
+    }
+
+    #[test]
+    fn writes_through_raw_pointer_are_visible_to_consumer() {
+        loom::model(|| {
+            let entries: Vec<_> = (0..2).map(|_| UnsafeCell::new(0u32)).collect();
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 2 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let mut entry = producer.reserve().unwrap();
+            let ptr = entry.as_mut_ptr();
+            // SAFETY: the slot is reserved, so nothing else accesses it until it is committed.
+            unsafe { ptr.write(42) };
+            producer.commit(entry).unwrap();
+
+            let entry = consumer.reserve().unwrap();
+            // SAFETY: the slot is reserved, so nothing writes to it until it is committed.
+            assert_eq!(unsafe { *entry.get() }, 42);
+            consumer.commit(entry).unwrap();
+        });
+    }
+
+    #[test]
+    fn batches_iterate_across_the_wrap() {
+        loom::model(|| {
+            let entries: Vec<_> = (0..4).map(|_| AtomicU32::new(0)).collect();
+            // start halfway through the ring, so the batch wraps around the end
+            let head = AtomicU32::new(2);
+            let tail = AtomicU32::new(2);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            let batch = producer.reserve_fair(4).unwrap();
+            assert_eq!(batch.iter().len(), 4);
+            for (entry, value) in (&batch).into_iter().zip(1..) {
+                entry.store(value, Ordering::Relaxed);
+            }
+            producer.commit_batch(batch).unwrap();
+
+            for value in 1..=4 {
+                let entry = consumer.reserve().unwrap();
+                assert_eq!(entry.load(Ordering::Relaxed), value);
+                consumer.commit(entry).unwrap();
+            }
+        });
+    }
+
     #[test]
     fn producer_and_consumer_work_together_to_avoid_deadlocks() {
         let mut model = loom::model::Builder::new();
@@ -128,3 +559,54 @@ mod test {
         });
     }
 }
+
+#[cfg(feature = "internal_benches")]
+mod benches {
+    use std::thread;
+
+    use divan::{Bencher, counter::ItemsCount};
+
+    use super::{BackoffStrategy, RingBufferConsumer, RingBufferProducer};
+    use crate::sync::atomic::{AtomicU32, Ordering};
+
+    const LENGTHS: &[usize] = &[64, 1024];
+
+    #[divan::bench(consts = LENGTHS)]
+    fn producer_to_consumer<const N: usize>(bencher: Bencher) {
+        const ITEMS: u32 = 1 << 16;
+
+        let entries: Vec<_> = (0..N).map(|_| AtomicU32::new(0)).collect();
+        let mask = u32::try_from(N).unwrap() - 1;
+
+        bencher.counter(ItemsCount::new(ITEMS)).bench(|| {
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+            let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+            thread::scope(|s| {
+                s.spawn(|| {
+                    for value in 0..ITEMS {
+                        let entry = producer.reserve_blocking(BackoffStrategy::default());
+                        entry.store(value, Ordering::Relaxed);
+                        producer.commit(entry).unwrap();
+                    }
+                });
+
+                s.spawn(|| {
+                    let mut expected = 0;
+                    while expected < ITEMS {
+                        let processed = consumer.consume(u32::MAX, |entry| {
+                            assert_eq!(entry.load(Ordering::Relaxed), expected);
+                            expected += 1;
+                        });
+
+                        if processed == 0 {
+                            thread::yield_now();
+                        }
+                    }
+                });
+            });
+        });
+    }
+}
@@ -0,0 +1,436 @@
+//! A ring buffer which owns its entries, head, and tail.
+//!
+//! Unlike [`RingBufferProducer`] and [`RingBufferConsumer`], which operate on memory kept alive
+//! elsewhere (typically shared with the kernel), an `OwnedRingBuffer` is self-contained. This is
+//! useful for tests and for small queues that don't involve `io_uring`.
+
+use std::sync::atomic::Ordering;
+
+use super::storage::Storage;
+use super::{RingBuffer, RingBufferConsumer, RingBufferError, RingBufferProducer};
+use crate::sync::atomic::AtomicU32;
+
+/// A ring buffer which owns its entries, head, and tail.
+///
+/// Access is provided through a [`RingBufferProducer`] and [`RingBufferConsumer`] pair, given to
+/// a closure by [`Self::with_split`].
+#[derive(Debug)]
+pub struct OwnedRingBuffer<T> {
+    entries: Storage<T>,
+    head: AtomicU32,
+    tail: AtomicU32,
+    mask: u32,
+}
+
+impl<T: Default> OwnedRingBuffer<T> {
+    /// Creates a new, empty `OwnedRingBuffer` with room for at least `capacity` entries. The
+    /// capacity is rounded to the next power of two.
+    ///
+    /// # Errors
+    /// - if `capacity` is zero, the [`RingBufferError::EmptyRing`] error is returned.
+    /// - if the rounded capacity is greater than `u32::MAX`, the
+    ///   [`RingBufferError::EntriesSliceTooLong`] error is returned.
+    pub fn with_capacity(capacity: usize) -> Result<Self, RingBufferError> {
+        Self::with_capacity_aligned(capacity, align_of::<T>())
+    }
+
+    /// Creates a new, empty `OwnedRingBuffer` with room for at least `capacity` entries, whose
+    /// storage is aligned to at least `align` bytes. The capacity is rounded to the next power of
+    /// two.
+    ///
+    /// Aligning entries to cache lines or pages is useful when they are handed to the kernel, such
+    /// as buffers registered for fixed I/O.
+    ///
+    /// # Errors
+    /// - if `capacity` is zero, the [`RingBufferError::EmptyRing`] error is returned.
+    /// - if the rounded capacity is greater than `u32::MAX`, or the storage would be too large to
+    ///   allocate, the [`RingBufferError::EntriesSliceTooLong`] error is returned.
+    /// - if `align` is not a power of two, the [`RingBufferError::InvalidAlignment`] error is
+    ///   returned.
+    pub fn with_capacity_aligned(capacity: usize, align: usize) -> Result<Self, RingBufferError> {
+        if capacity == 0 {
+            return Err(RingBufferError::EmptyRing);
+        }
+
+        let capacity = capacity
+            .checked_next_power_of_two()
+            .ok_or(RingBufferError::EntriesSliceTooLong)?;
+        let mask = RingBuffer::mask_for(capacity)?;
+
+        Ok(Self {
+            entries: Storage::new_aligned(capacity, align, T::default)?,
+            head: AtomicU32::new(0),
+            tail: AtomicU32::new(0),
+            mask,
+        })
+    }
+
+    /// Reallocates the ring buffer with room for at least `new_capacity` entries, keeping the
+    /// alignment it was created with. The capacity is rounded to the next power of two.
+    ///
+    /// Only an empty ring buffer can be resized, so no committed entries are lost. The head and
+    /// tail are reset to zero.
+    ///
+    /// # Errors
+    /// - if the ring buffer is not empty, the [`RingBufferError::NotEmpty`] error is returned.
+    /// - if `new_capacity` is zero, the [`RingBufferError::EmptyRing`] error is returned.
+    /// - if the rounded capacity is greater than `u32::MAX`, or the storage would be too large to
+    ///   allocate, the [`RingBufferError::EntriesSliceTooLong`] error is returned.
+    ///
+    /// The ring buffer is left unchanged if an error is returned.
+    pub fn resize(&mut self, new_capacity: usize) -> Result<(), RingBufferError> {
+        if !self.is_empty() {
+            return Err(RingBufferError::NotEmpty);
+        }
+
+        *self = Self::with_capacity_aligned(new_capacity, self.entries.align())?;
+
+        Ok(())
+    }
+}
+
+impl<T> OwnedRingBuffer<T> {
+    /// Creates a new, empty `OwnedRingBuffer` which reuses `entries` as its storage, such as one
+    /// reclaimed from another ring buffer with [`Self::into_storage`].
+    ///
+    /// Entries keep whatever values they hold, but the head and tail start at zero, so none are
+    /// occupied. This avoids reallocating and reinitializing entries when many short-lived ring
+    /// buffers are created, such as from a pool.
+    ///
+    /// # Errors
+    /// - if `entries` is empty, the [`RingBufferError::EmptyRing`] error is returned.
+    /// - if `entries.len()` is greater than `u32::MAX`, the
+    ///   [`RingBufferError::EntriesSliceTooLong`] error is returned.
+    /// - `entries.len()` must be a power of two. If this is not the case, the
+    ///   [`RingBufferError::LengthNotPowerOfTwo`] error is returned.
+    ///
+    /// `entries` is dropped if an error is returned.
+    pub fn from_storage(entries: Box<[T]>) -> Result<Self, RingBufferError> {
+        let mask = RingBuffer::mask_for(entries.len())?;
+
+        Ok(Self {
+            entries: Storage::from_boxed(entries),
+            head: AtomicU32::new(0),
+            tail: AtomicU32::new(0),
+            mask,
+        })
+    }
+
+    /// Consumes the ring buffer, returning its entries so the allocation can be reused with
+    /// [`Self::from_storage`].
+    ///
+    /// Occupied entries are returned as they are. If the ring buffer was created with an alignment
+    /// larger than that of `T`, the entries are moved into a new allocation, which has only the
+    /// alignment of `T`.
+    #[must_use]
+    pub fn into_storage(self) -> Box<[T]> {
+        self.entries.into_boxed()
+    }
+
+    /// Get the size of the ring buffer.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Get the capacity of the ring buffer. This is an alias of [`Self::size`].
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.size()
+    }
+
+    /// Get the number of occupied entries in the ring buffer.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tail
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.head.load(Ordering::Acquire)) as usize
+    }
+
+    /// Determines if the ring buffer has no occupied entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Splits the ring buffer into a producer and consumer, passing both to `f`.
+    ///
+    /// The producer and consumer only live for the duration of `f`. Entries which were reserved,
+    /// but not committed, when `f` returns are released, and may be reserved again by the next
+    /// split. Taking `&mut self` ensures only one producer and consumer pair exists at a time.
+    pub fn with_split<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&RingBufferProducer<'_, T>, &RingBufferConsumer<'_, T>) -> R,
+    {
+        // the mask was derived from the length of entries on construction, so neither side can
+        // fail validation
+        let split = RingBufferProducer::new(&self.entries, &self.head, &self.tail, self.mask)
+            .and_then(|producer| {
+                RingBufferConsumer::new(&self.entries, &self.head, &self.tail, self.mask)
+                    .map(|consumer| (producer, consumer))
+            });
+
+        match split {
+            Ok((producer, consumer)) => f(&producer, &consumer),
+            Err(e) => unreachable!("owned ring buffer is always valid: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::sync::atomic::{AtomicU32, Ordering};
+    use crate::{OwnedRingBuffer, RingBufferError};
+
+    #[test]
+    fn with_capacity_rounds_to_next_power_of_two() {
+        loom::model(|| {
+            let ring = OwnedRingBuffer::<u32>::with_capacity(3).unwrap();
+
+            assert_eq!(ring.size(), 4);
+            assert_eq!(ring.capacity(), 4);
+            assert!(ring.is_empty());
+        });
+    }
+
+    #[test]
+    fn with_capacity_rejects_zero() {
+        loom::model(|| {
+            let result = OwnedRingBuffer::<u32>::with_capacity(0);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::EmptyRing));
+        });
+    }
+
+    #[test]
+    fn with_capacity_rejects_sizes_too_large_to_round() {
+        loom::model(|| {
+            for capacity in [u32::MAX as usize, usize::MAX] {
+                let result = OwnedRingBuffer::<u32>::with_capacity(capacity);
+
+                assert!(result.is_err_and(|e| e == RingBufferError::EntriesSliceTooLong));
+            }
+        });
+    }
+
+    #[test]
+    fn with_capacity_aligned_aligns_storage() {
+        loom::model(|| {
+            const ALIGN: usize = 4096;
+            let mut ring = OwnedRingBuffer::<AtomicU32>::with_capacity_aligned(4, ALIGN).unwrap();
+
+            assert_eq!(ring.entries.as_ptr().addr() % ALIGN, 0);
+
+            ring.with_split(|producer, consumer| {
+                let entry = producer.reserve().unwrap();
+                entry.store(7, Ordering::Relaxed);
+                producer.commit(entry).unwrap();
+
+                let entry = consumer.reserve().unwrap();
+                assert_eq!(entry.load(Ordering::Relaxed), 7);
+                consumer.commit(entry).unwrap();
+            });
+        });
+    }
+
+    #[test]
+    fn with_capacity_aligned_rejects_alignment_not_power_of_two() {
+        loom::model(|| {
+            let result = OwnedRingBuffer::<u32>::with_capacity_aligned(4, 3);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::InvalidAlignment));
+        });
+    }
+
+    #[test]
+    fn with_capacity_aligned_supports_zero_sized_entries() {
+        loom::model(|| {
+            const ALIGN: usize = 64;
+            let ring = OwnedRingBuffer::<()>::with_capacity_aligned(4, ALIGN).unwrap();
+
+            assert_eq!(ring.entries.as_ptr().addr() % ALIGN, 0);
+            assert_eq!(ring.size(), 4);
+        });
+    }
+
+    #[test]
+    fn resize_grows_and_shrinks_empty_ring() {
+        loom::model(|| {
+            const ALIGN: usize = 4096;
+            let mut ring = OwnedRingBuffer::<AtomicU32>::with_capacity_aligned(4, ALIGN).unwrap();
+            ring.with_split(|producer, consumer| {
+                let entry = producer.reserve().unwrap();
+                producer.commit(entry).unwrap();
+                let entry = consumer.reserve().unwrap();
+                consumer.commit(entry).unwrap();
+            });
+
+            ring.resize(9).unwrap();
+            assert_eq!(ring.size(), 16);
+            assert_eq!(ring.entries.as_ptr().addr() % ALIGN, 0);
+
+            ring.resize(2).unwrap();
+            assert_eq!(ring.size(), 2);
+            assert_eq!(ring.entries.as_ptr().addr() % ALIGN, 0);
+
+            ring.with_split(|producer, consumer| {
+                for value in 1..=2 {
+                    let entry = producer.reserve().unwrap();
+                    entry.store(value, Ordering::Relaxed);
+                    producer.commit(entry).unwrap();
+                }
+                assert!(producer.reserve().is_none());
+
+                for value in 1..=2 {
+                    let entry = consumer.reserve().unwrap();
+                    assert_eq!(entry.load(Ordering::Relaxed), value);
+                    consumer.commit(entry).unwrap();
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn resize_rejects_non_empty_ring() {
+        loom::model(|| {
+            let mut ring = OwnedRingBuffer::<AtomicU32>::with_capacity(2).unwrap();
+            ring.with_split(|producer, _| {
+                let entry = producer.reserve().unwrap();
+                entry.store(7, Ordering::Relaxed);
+                producer.commit(entry).unwrap();
+            });
+
+            let result = ring.resize(8);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::NotEmpty));
+            assert_eq!(ring.size(), 2);
+            assert_eq!(ring.len(), 1);
+        });
+    }
+
+    #[test]
+    fn resize_rejects_zero() {
+        loom::model(|| {
+            let mut ring = OwnedRingBuffer::<u32>::with_capacity(2).unwrap();
+
+            let result = ring.resize(0);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::EmptyRing));
+            assert_eq!(ring.size(), 2);
+        });
+    }
+
+    #[test]
+    fn into_storage_can_be_reused_by_another_ring() {
+        loom::model(|| {
+            let mut ring = OwnedRingBuffer::<AtomicU32>::with_capacity(4).unwrap();
+            ring.with_split(|producer, consumer| {
+                for value in 1..=3 {
+                    let entry = producer.reserve().unwrap();
+                    entry.store(value, Ordering::Relaxed);
+                    producer.commit(entry).unwrap();
+                }
+                let entry = consumer.reserve().unwrap();
+                consumer.commit(entry).unwrap();
+            });
+
+            let storage = ring.into_storage();
+            let addr = storage.as_ptr().addr();
+            assert_eq!(storage.len(), 4);
+
+            let mut ring = OwnedRingBuffer::from_storage(storage).unwrap();
+            assert_eq!(ring.entries.as_ptr().addr(), addr);
+            assert_eq!(ring.size(), 4);
+            assert!(ring.is_empty());
+
+            ring.with_split(|producer, consumer| {
+                for value in 1..=4 {
+                    let entry = producer.reserve().unwrap();
+                    entry.store(value * 10, Ordering::Relaxed);
+                    producer.commit(entry).unwrap();
+                }
+                assert!(producer.reserve().is_none());
+
+                for value in 1..=4 {
+                    let entry = consumer.reserve().unwrap();
+                    assert_eq!(entry.load(Ordering::Relaxed), value * 10);
+                    consumer.commit(entry).unwrap();
+                }
+            });
+        });
+    }
+
+    #[test]
+    fn into_storage_moves_over_aligned_entries() {
+        loom::model(|| {
+            let mut ring = OwnedRingBuffer::<AtomicU32>::with_capacity_aligned(2, 4096).unwrap();
+            ring.with_split(|producer, _| {
+                let entry = producer.reserve().unwrap();
+                entry.store(7, Ordering::Relaxed);
+                producer.commit(entry).unwrap();
+            });
+
+            let storage = ring.into_storage();
+
+            assert_eq!(storage.len(), 2);
+            assert_eq!(storage[0].load(Ordering::Relaxed), 7);
+        });
+    }
+
+    #[test]
+    fn from_storage_rejects_invalid_lengths() {
+        loom::model(|| {
+            let result = OwnedRingBuffer::<u32>::from_storage(Box::new([]));
+            assert!(result.is_err_and(|e| e == RingBufferError::EmptyRing));
+
+            let result = OwnedRingBuffer::from_storage(vec![0u32; 3].into_boxed_slice());
+            assert!(result.is_err_and(|e| e == RingBufferError::LengthNotPowerOfTwo));
+        });
+    }
+
+    #[test]
+    fn produces_and_consumes_entries() {
+        loom::model(|| {
+            let mut ring = OwnedRingBuffer::<AtomicU32>::with_capacity(4).unwrap();
+
+            ring.with_split(|producer, consumer| {
+                for value in 1..=4 {
+                    let entry = producer.reserve().unwrap();
+                    entry.store(value, Ordering::Relaxed);
+                    producer.commit(entry).unwrap();
+                }
+                assert!(producer.reserve().is_none());
+
+                for value in 1..=4 {
+                    let entry = consumer.reserve().unwrap();
+                    assert_eq!(entry.load(Ordering::Relaxed), value);
+                    consumer.commit(entry).unwrap();
+                }
+                assert!(consumer.reserve().is_none());
+            });
+
+            assert!(ring.is_empty());
+        });
+    }
+
+    #[test]
+    fn keeps_entries_between_splits() {
+        loom::model(|| {
+            let mut ring = OwnedRingBuffer::<AtomicU32>::with_capacity(2).unwrap();
+
+            ring.with_split(|producer, _| {
+                let entry = producer.reserve().unwrap();
+                entry.store(7, Ordering::Relaxed);
+                producer.commit(entry).unwrap();
+            });
+            assert_eq!(ring.len(), 1);
+
+            ring.with_split(|_, consumer| {
+                let entry = consumer.reserve().unwrap();
+                assert_eq!(entry.load(Ordering::Relaxed), 7);
+                consumer.commit(entry).unwrap();
+            });
+            assert!(ring.is_empty());
+        });
+    }
+}
@@ -5,8 +5,10 @@
 
 use std::sync::atomic::Ordering;
 
-use super::{ReservedEntry, RingBufferError};
+use super::base::RingBufferCore;
+use super::{BackoffStrategy, ReservedBatch, ReservedEntry, RingBufferError};
 use crate::sync::atomic::AtomicU32;
+use crate::sync::hint;
 
 /// A thread-safe and lock-free ring buffer producer with two-stage commit.
 ///
@@ -14,18 +16,21 @@ use crate::sync::atomic::AtomicU32;
 /// the slot, gives it to the caller to write data, then commits the slot to the consumer.
 #[derive(Debug)]
 pub struct RingBufferProducer<'ring, T> {
-    head: &'ring AtomicU32,
-    tail: &'ring AtomicU32,
+    core: RingBufferCore<'ring, T>,
     uncommitted_tail: AtomicU32,
-    entries: &'ring [T],
-    mask: u32,
-    shift: u32,
+    // consecutive reservations lost to another thread, for `reserve_adaptive`
+    contention: AtomicU32,
+    adaptive_threshold: u32,
+    adaptive_batch: u32,
 }
 
 impl<'ring, T> RingBufferProducer<'ring, T> {
     /// Creates a new `RingBufferProducer`, taking existing indicies for the head and tail.
     ///
+    /// `entries` must have a length of at least one.
+    ///
     /// # Errors
+    /// - if `entries` is empty, the [`RingBufferError::EmptyRing`] error is returned.
     /// - if `entries.len()` is greater than `u32::MAX`, the
     ///   [`RingBufferError::EntriesSliceTooLong`] error is returned.
     /// - `entries.len()` must be a power of two. If this is not the case, the
@@ -38,12 +43,16 @@ impl<'ring, T> RingBufferProducer<'ring, T> {
         tail: &'ring AtomicU32,
         mask: u32,
     ) -> Result<Self, RingBufferError> {
-        Self::new_internal(entries, head, tail, mask, false)
+        RingBufferCore::new(entries, head, tail, mask, false).map(Self::from_core)
     }
 
     /// Creates a new `RingBufferProducer` for large objects that span two entries, taking existing indicies for the head and tail.
     ///
+    /// `entries` must have a length of at least two, making up a single large object.
+    ///
     /// # Errors
+    /// - if `entries` has fewer than two elements, the [`RingBufferError::EmptyRing`] error is
+    ///   returned.
     /// - if `entries.len()` is greater than `u32::MAX`, the
     ///   [`RingBufferError::EntriesSliceTooLong`] error is returned.
     /// - `entries.len()` must be a power of two. If this is not the case, the
@@ -56,89 +65,285 @@ impl<'ring, T> RingBufferProducer<'ring, T> {
         tail: &'ring AtomicU32,
         mask: u32,
     ) -> Result<Self, RingBufferError> {
-        Self::new_internal(entries, head, tail, mask, true)
+        RingBufferCore::new(entries, head, tail, mask, true).map(Self::from_core)
     }
 
-    fn new_internal(
+    /// Creates a new `RingBufferProducer` for large objects that span two entries, where `mask` covers
+    /// the large objects rather than every entry, taking existing indicies for the head and tail.
+    ///
+    /// This matches the ring mask reported by the kernel for rings set up with 128-byte SQEs or
+    /// 32-byte CQEs, where `entries` holds two slots for every object the mask can index. Unlike
+    /// [`Self::new_big`], which takes a mask over every entry, this verifies `entries.len()` is
+    /// exactly twice the capacity described by `mask`.
+    ///
+    /// # Errors
+    /// - if `entries` has fewer than two elements, the [`RingBufferError::EmptyRing`] error is
+    ///   returned.
+    /// - if `entries.len()` is greater than `u32::MAX`, the
+    ///   [`RingBufferError::EntriesSliceTooLong`] error is returned.
+    /// - `entries.len()` must be a power of two. If this is not the case, the
+    ///   [`RingBufferError::LengthNotPowerOfTwo`] error is returned.
+    /// - `entries.len()` must be exactly `2 * (mask + 1)`. If this is not the case, the
+    ///   [`RingBufferError::InvalidMaskValue`] error is returned.
+    pub fn new_big_checked(
         entries: &'ring [T],
         head: &'ring AtomicU32,
         tail: &'ring AtomicU32,
         mask: u32,
-        big: bool,
     ) -> Result<Self, RingBufferError> {
-        if entries.len() as u64 > u64::from(u32::MAX) {
-            return Err(RingBufferError::EntriesSliceTooLong);
-        }
-        if (entries.len() as u64).next_power_of_two() != entries.len() as u64 {
-            return Err(RingBufferError::LengthNotPowerOfTwo);
-        }
-        if mask as usize != entries.len() - 1 {
-            return Err(RingBufferError::InvalidMaskValue);
-        }
+        RingBufferCore::new_big_checked(entries, head, tail, mask).map(Self::from_core)
+    }
 
-        let uncommitted_tail = AtomicU32::new(tail.load(Ordering::Relaxed));
+    fn from_core(core: RingBufferCore<'ring, T>) -> Self {
+        let uncommitted_tail = AtomicU32::new(core.tail.load(Ordering::Relaxed));
 
-        Ok(Self {
-            head,
-            tail,
+        Self {
+            core,
             uncommitted_tail,
-            entries,
-            mask,
-            shift: u32::from(big),
-        })
+            contention: AtomicU32::new(0),
+            adaptive_threshold: 4,
+            adaptive_batch: 8,
+        }
+    }
+
+    /// Configure [`Self::reserve_adaptive`] to reserve batches of up to `batch` entries once
+    /// `threshold` consecutive reservations have been lost to other threads.
+    ///
+    /// Defaults to batches of 8 entries after 4 consecutive losses.
+    #[must_use]
+    pub fn with_adaptive_reserve(mut self, threshold: u32, batch: u32) -> Self {
+        self.adaptive_threshold = threshold;
+        self.adaptive_batch = batch;
+
+        self
     }
 
     /// Get the size of the ring buffer.
     #[must_use]
     pub fn size(&self) -> usize {
-        self.entries.len() >> self.shift
+        self.core.size()
     }
 
     /// Get the number of available entries between tail and head. This represents the number of
     /// entries that can currently be reserved.
     #[must_use]
     pub fn available(&self) -> u32 {
-        self.tail
+        self.core.available()
+    }
+
+    /// Get the capacity of the ring buffer. This is an alias of [`Self::size`].
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.size()
+    }
+
+    /// Get the number of occupied entries in the ring buffer. These are entries which have been
+    /// committed by the producing side, but not yet committed by the consuming side.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.available() as usize
+    }
+
+    /// Determines if the ring buffer has no occupied entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the number of entries which have been reserved, but not yet committed.
+    ///
+    /// A large gap which persists indicates a thread reserved entries and then stalled before
+    /// committing them, holding up every entry reserved after it.
+    #[must_use]
+    pub fn pending_commits(&self) -> u32 {
+        self.uncommitted_tail
             .load(Ordering::Acquire)
-            .wrapping_sub(self.head.load(Ordering::Acquire))
+            .wrapping_sub(self.core.tail.load(Ordering::Acquire))
     }
 
-    /// Determines if the ring buffer is empty, or has no more elements to reserve.
+    /// Get the entire backing slice of entries, for debugging and inspection.
     ///
-    /// If this is true, the consuming side of the ring buffer must consume entries to free up
-    /// space. If this returns false, a [`Self::reserve`] operation is only guaranteed to succeed
+    /// # Caveats
+    /// This exposes every slot, whether it is free, reserved, or committed, and says nothing
+    /// about which is which. While another thread, the consumer side, or the kernel may be using
+    /// the ring, slots can change at any moment, so the contents are only meaningful when the
+    /// caller knows nothing else is accessing the ring. For rings created with `new_big`, each
+    /// entry spans two slots.
+    #[must_use]
+    pub fn entries(&self) -> &'ring [T] {
+        self.core.entries
+    }
+
+    /// Determines if the ring buffer has space for another entry, as fewer than [`Self::size`]
+    /// entries are occupied.
+    ///
+    /// If this returns false, the consuming side of the ring buffer must consume entries to free
+    /// up space. If this returns true, a [`Self::reserve`] operation is only guaranteed to succeed
     /// if there is only one thread producing on this ring buffer.
     #[must_use]
+    pub fn has_space(&self) -> bool {
+        (self.available() as usize) < self.size()
+    }
+
+    /// Determines if the ring buffer has space for another entry. Despite its name, this returns
+    /// true while the ring is not full, rather than when it is empty.
+    #[deprecated(note = "use `has_space`, or `is_empty` to check for no occupied entries")]
+    #[must_use]
     pub fn empty(&self) -> bool {
-        (self.available() as usize) < self.entries.len()
+        self.has_space()
     }
 
     /// Reserve an entry.
     ///
     /// Produces [`Option::Some`] if an entry was successfully reserved. Otherwise returns
     /// [`Option::None`] if the ring has no more space, or another thread reserved the same entry
-    /// first.
+    /// first. Use [`Self::try_reserve`] to tell the two apart.
     #[must_use]
     pub fn reserve(&self) -> Option<ReservedEntry<'ring, T>> {
-        let head = self.head.load(Ordering::Acquire);
+        self.try_reserve().ok()
+    }
+
+    /// Reserve an entry, reporting why a reservation could not be made.
+    ///
+    /// # Errors
+    /// - If the ring has no more space, returns [`RingBufferError::Full`].
+    /// - If another thread reserved the same entry first, returns [`RingBufferError::Contended`].
+    ///   Retrying the operation may succeed.
+    pub fn try_reserve(&self) -> Result<ReservedEntry<'ring, T>, RingBufferError> {
+        let head = self.core.head.load(Ordering::Acquire);
         let tail = self.uncommitted_tail.load(Ordering::Acquire);
 
-        if tail.wrapping_sub(head) as usize >= self.entries.len() {
-            None
+        if tail.wrapping_sub(head) as usize >= self.size() {
+            Err(RingBufferError::Full)
         } else {
-            let entry = &self.entries[((tail & self.mask) << self.shift) as usize];
+            let entry = self.core.entry(tail);
             if self
                 .uncommitted_tail
-                .compare_exchange(tail, tail + 1, Ordering::Release, Ordering::Relaxed)
+                .compare_exchange(
+                    tail,
+                    tail.wrapping_add(1),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                )
                 .is_err()
             {
-                None
+                Err(RingBufferError::Contended)
             } else {
-                Some(ReservedEntry::new(tail, entry))
+                Ok(ReservedEntry::new(tail, entry))
             }
         }
     }
 
+    /// Reserve an entry, retrying up to `attempts` times if another thread reserved it first.
+    ///
+    /// Unlike [`Self::reserve_blocking`], this gives up immediately once the ring is full, and
+    /// only spins between attempts rather than yielding, since losing a race implies space is
+    /// still available for the next attempt.
+    ///
+    /// Returns [`Option::None`] if the ring has no more space, or every attempt was contended.
+    #[must_use]
+    pub fn reserve_spin(&self, attempts: u32) -> Option<ReservedEntry<'ring, T>> {
+        for _ in 0..attempts {
+            match self.try_reserve() {
+                Ok(entry) => return Some(entry),
+                Err(RingBufferError::Contended) => hint::spin_loop(),
+                Err(_) => return None,
+            }
+        }
+
+        None
+    }
+
+    /// Reserve an entry, waiting according to `strategy` until one is available.
+    ///
+    /// This never returns if the consuming side stops consuming entries.
+    #[must_use]
+    pub fn reserve_blocking(&self, strategy: BackoffStrategy) -> ReservedEntry<'ring, T> {
+        let mut step = 0u32;
+
+        loop {
+            if let Some(entry) = self.reserve() {
+                return entry;
+            }
+
+            strategy.snooze(step);
+            step = step.saturating_add(1);
+        }
+    }
+
+    /// Reserve a contiguous run of up to `max` entries.
+    ///
+    /// Claiming several entries with a single compare-and-swap reduces contention when many
+    /// threads are producing batches. If fewer than `max` entries are free, only the free entries
+    /// are reserved; the number actually reserved is given by [`ReservedBatch::len`].
+    ///
+    /// Produces [`Option::Some`] if at least one entry was successfully reserved. Otherwise returns
+    /// [`Option::None`] if `max` is zero, the ring has no more space, or another thread reserved
+    /// the same entries first.
+    #[must_use]
+    pub fn reserve_fair(&self, max: u32) -> Option<ReservedBatch<'ring, T>> {
+        self.try_reserve_batch(max).ok()
+    }
+
+    /// Reserve a single entry, switching to batches when other threads keep winning the race.
+    ///
+    /// Under heavy contention, every thread can lose the compare-and-swap on the tail repeatedly.
+    /// Once enough consecutive reservations have been lost, as configured by
+    /// [`Self::with_adaptive_reserve`], this reserves a run of entries in a single
+    /// compare-and-swap instead, so fewer contended operations are needed per entry. It goes
+    /// back to single entries as soon as a reservation succeeds.
+    ///
+    /// The whole run is returned to the caller, which must commit it as usual. Nothing is kept
+    /// back in the producer, so other ways of reserving entries can be freely mixed in.
+    ///
+    /// Returns [`Option::None`] if the ring has no more space, or another thread reserved the same
+    /// entries first.
+    #[must_use]
+    pub fn reserve_adaptive(&self) -> Option<ReservedBatch<'ring, T>> {
+        let contention = self.contention.load(Ordering::Relaxed);
+        let max = if contention >= self.adaptive_threshold {
+            self.adaptive_batch.max(1)
+        } else {
+            1
+        };
+
+        match self.try_reserve_batch(max) {
+            Ok(batch) => {
+                if contention != 0 {
+                    self.contention.store(0, Ordering::Relaxed);
+                }
+                Some(batch)
+            }
+            Err(RingBufferError::Contended) => {
+                self.contention.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    fn try_reserve_batch(&self, max: u32) -> Result<ReservedBatch<'ring, T>, RingBufferError> {
+        let head = self.core.head.load(Ordering::Acquire);
+        let tail = self.uncommitted_tail.load(Ordering::Acquire);
+
+        let free = self.size().saturating_sub(tail.wrapping_sub(head) as usize);
+        let len = u32::try_from(free).map_or(max, |free| free.min(max));
+
+        if len == 0 {
+            return Err(RingBufferError::Full);
+        }
+
+        self.uncommitted_tail
+            .compare_exchange(
+                tail,
+                tail.wrapping_add(len),
+                Ordering::Release,
+                Ordering::Relaxed,
+            )
+            .map(|_| self.core.batch(tail, len))
+            .map_err(|_| RingBufferError::Contended)
+    }
+
     /// Commit the reserved entry.
     ///
     /// Ensures the reserved entry is next to be committed, then advances the tail of the ring,
@@ -151,22 +356,112 @@ impl<'ring, T> RingBufferProducer<'ring, T> {
     // Taking `entry` by value is intended to ensure access is no longer possible after committing.
     #[allow(clippy::needless_pass_by_value)]
     pub fn commit(&self, entry: ReservedEntry<'ring, T>) -> Result<(), RingBufferError> {
-        if entry.index != self.tail.load(Ordering::Acquire) {
+        self.debug_assert_reserved(entry.index, 1);
+
+        if entry.index != self.core.tail.load(Ordering::Acquire) {
             return Err(RingBufferError::CommitOutOfOrder);
         }
 
-        self.tail.fetch_add(1, Ordering::Release);
+        self.core.tail.fetch_add(1, Ordering::Release);
         Ok(())
     }
+
+    /// Commit every reserved entry from the current tail up to and including `last`.
+    ///
+    /// This is a fast path for a single producer which reserved several entries individually: the
+    /// tail is advanced past all of them with a single store, rather than one store per entry.
+    /// Every entry between the current tail and `last` must have been reserved by the caller, and
+    /// must not be committed separately. It is not suitable for multiple producers, as it would
+    /// commit entries another thread has reserved but not yet written.
+    ///
+    /// # Errors
+    /// - If `last` is not a reserved entry at or after the current tail, because it was reserved
+    ///   from another ring or has already been committed past, returns `last` along with
+    ///   [`RingBufferError::CommitOutOfOrder`].
+    pub fn commit_upto(
+        &self,
+        last: ReservedEntry<'ring, T>,
+    ) -> Result<(), (ReservedEntry<'ring, T>, RingBufferError)> {
+        let tail = self.core.tail.load(Ordering::Acquire);
+        let reserved = self
+            .uncommitted_tail
+            .load(Ordering::Acquire)
+            .wrapping_sub(tail);
+
+        if last.index.wrapping_sub(tail) >= reserved {
+            return Err((last, RingBufferError::CommitOutOfOrder));
+        }
+
+        self.core
+            .tail
+            .store(last.index.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+
+    /// Commit the reserved batch.
+    ///
+    /// Ensures the first entry of the batch is next to be committed, then advances the tail of the
+    /// ring past every entry in the batch, making them visible to the consumer side.
+    ///
+    /// # Errors
+    /// - If `batch` does not start at the next entry to be committed, because another thread
+    ///   reserved entries before it that have not yet been committed, returns
+    ///   [`RingBufferError::CommitOutOfOrder`].
+    // Taking `batch` by value is intended to ensure access is no longer possible after committing.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn commit_batch(&self, batch: ReservedBatch<'ring, T>) -> Result<(), RingBufferError> {
+        self.debug_assert_reserved(batch.index, batch.len);
+
+        if batch.index != self.core.tail.load(Ordering::Acquire) {
+            return Err(RingBufferError::CommitOutOfOrder);
+        }
+
+        self.core.tail.fetch_add(batch.len, Ordering::Release);
+        Ok(())
+    }
+
+    /// Determines if this producer advances `tail`.
+    pub(super) fn shares_tail(&self, tail: &AtomicU32) -> bool {
+        std::ptr::eq(self.core.tail, tail)
+    }
+
+    /// Resume reserving from `index`, after the tail was moved back to it by the consumer side.
+    pub(super) fn rewind(&mut self, index: u32) {
+        self.uncommitted_tail.store(index, Ordering::Release);
+    }
+
+    /// Panics in debug builds if the `len` entries starting at `index` are not outstanding
+    /// reservations of this producer.
+    ///
+    /// Committing such entries is never valid, whatever order the reservations are committed in,
+    /// so retrying on [`RingBufferError::CommitOutOfOrder`] would never succeed, and the entries
+    /// could even be accepted while another producer is still writing them.
+    #[track_caller]
+    fn debug_assert_reserved(&self, index: u32, len: u32) {
+        if cfg!(debug_assertions) {
+            let tail = self.core.tail.load(Ordering::Acquire);
+            let uncommitted_tail = self.uncommitted_tail.load(Ordering::Acquire);
+            let reserved = uncommitted_tail.wrapping_sub(tail);
+
+            assert!(
+                len <= reserved && index.wrapping_sub(tail) <= reserved - len,
+                "impossible commit order: expected entries reserved in {tail}..{uncommitted_tail}, \
+                 got {index}..{}",
+                index.wrapping_add(len),
+            );
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
     use loom::thread::{self, yield_now};
 
     use crate::sync::Arc;
     use crate::sync::atomic::{AtomicU32, Ordering};
-    use crate::{RingBufferError, RingBufferProducer};
+    use crate::{BackoffStrategy, RingBufferConsumer, RingBufferError, RingBufferProducer};
 
     #[test]
     fn new_returns_err_when_entries_is_larger_than_u32() {
@@ -210,6 +505,34 @@ mod test {
         });
     }
 
+    #[test]
+    fn new_returns_err_when_entries_is_empty() {
+        loom::model(|| {
+            let entries: Vec<u32> = vec![];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 0;
+
+            let result = RingBufferProducer::new(&entries, &head, &tail, mask);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::EmptyRing));
+        });
+    }
+
+    #[test]
+    fn new_big_returns_err_when_entries_has_one_element() {
+        loom::model(|| {
+            let entries = vec![0u32; 1];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 0;
+
+            let result = RingBufferProducer::new_big(&entries, &head, &tail, mask);
+
+            assert!(result.is_err_and(|e| e == RingBufferError::EmptyRing));
+        });
+    }
+
     #[test]
     fn new_size_returns_entries_len() {
         loom::model(|| {
@@ -282,6 +605,78 @@ mod test {
         });
     }
 
+    #[test]
+    fn capacity_returns_size() {
+        loom::model(|| {
+            let entries = vec![0u32; 32];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 32 - 1;
+            let producer = RingBufferProducer::new_big(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(producer.capacity(), producer.size());
+        });
+    }
+
+    #[test]
+    fn new_big_checked_takes_mask_over_large_objects() {
+        loom::model(|| {
+            let entries = vec![0u32; 32];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 16 - 1;
+
+            let producer =
+                RingBufferProducer::new_big_checked(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(producer.size(), 16);
+        });
+    }
+
+    #[test]
+    fn new_big_checked_rejects_mismatched_mask() {
+        loom::model(|| {
+            let entries = vec![0u32; 32];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+
+            // a mask over every entry, as `new_big` takes, describes twice the capacity
+            for mask in [32 - 1, 8 - 1, (1 << 31) | (16 - 1), u32::MAX] {
+                let result = RingBufferProducer::new_big_checked(&entries, &head, &tail, mask);
+
+                assert!(result.is_err_and(|e| e == RingBufferError::InvalidMaskValue));
+            }
+        });
+    }
+
+    #[test]
+    fn len_tracks_reserve_and_commit() {
+        loom::model(|| {
+            const ENTRIES: usize = 2;
+            let entries = vec![0u32; ENTRIES];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            assert_eq!(producer.len(), 0);
+            assert!(producer.is_empty());
+
+            let entry = producer.reserve().unwrap();
+            assert_eq!(producer.len(), 0);
+            producer.commit(entry).unwrap();
+            assert_eq!(producer.len(), 1);
+            assert!(!producer.is_empty());
+
+            assert!(producer.has_space());
+
+            let entry = producer.reserve().unwrap();
+            producer.commit(entry).unwrap();
+            assert_eq!(producer.len(), producer.size());
+            assert!(!producer.has_space());
+        });
+    }
+
     #[test]
     fn reserves_no_entries_when_none_are_available() {
         loom::model(|| {
@@ -297,6 +692,89 @@ mod test {
         });
     }
 
+    #[test]
+    fn try_reserve_returns_full_when_none_are_available() {
+        loom::model(|| {
+            let entries = vec![0u32; 32];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(32);
+            let mask = 32 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let result = producer.try_reserve();
+
+            assert!(result.is_err_and(|e| e == RingBufferError::Full));
+        });
+    }
+
+    #[test]
+    fn try_reserve_reports_contention_rather_than_full() {
+        loom::model(|| {
+            const ENTRIES: usize = 2;
+
+            // producers share a single `RingBufferProducer`, which must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![0u32; ENTRIES].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let producer = Arc::new(RingBufferProducer::new(entries, head, tail, mask).unwrap());
+
+            let other = {
+                let producer = producer.clone();
+                thread::spawn(move || producer.try_reserve().map(|entry| entry.index))
+            };
+            let this = producer.try_reserve().map(|entry| entry.index);
+            let other = other.join().unwrap();
+
+            // the ring has space for both, so a failure can only be a lost race
+            assert!(this.is_ok() || other.is_ok());
+            for result in [this, other] {
+                assert!(result.is_ok() || result == Err(RingBufferError::Contended));
+            }
+        });
+    }
+
+    #[test]
+    fn reserve_spin_retries_contended_reservations() {
+        loom::model(|| {
+            const ENTRIES: usize = 2;
+
+            // producers share a single `RingBufferProducer`, which must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![0u32; ENTRIES].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let producer = Arc::new(RingBufferProducer::new(entries, head, tail, mask).unwrap());
+
+            // each producer can lose the race at most once, so two attempts always succeed
+            let other = {
+                let producer = producer.clone();
+                thread::spawn(move || producer.reserve_spin(2).map(|entry| entry.index))
+            };
+            let this = producer.reserve_spin(2).map(|entry| entry.index);
+            let other = other.join().unwrap();
+
+            assert!(this.is_some() && other.is_some());
+            assert_ne!(this, other);
+        });
+    }
+
+    #[test]
+    fn reserve_spin_stops_when_full() {
+        loom::model(|| {
+            let entries = vec![0u32; 1];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 0;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let _entry = producer.reserve_spin(1).unwrap();
+
+            assert!(producer.reserve_spin(u32::MAX).is_none());
+            assert!(producer.reserve_spin(0).is_none());
+        });
+    }
+
     #[test]
     fn does_not_commit_tail_until_entry_is_returned() {
         loom::model(|| {
@@ -313,6 +791,51 @@ mod test {
         });
     }
 
+    #[test]
+    fn pending_commits_counts_uncommitted_reservations() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let first = producer.reserve().unwrap();
+            let _second = producer.reserve().unwrap();
+            let _third = producer.reserve().unwrap();
+            assert_eq!(producer.pending_commits(), 3);
+
+            producer.commit(first).unwrap();
+            assert_eq!(producer.pending_commits(), 2);
+        });
+    }
+
+    #[test]
+    fn entries_exposes_the_backing_slice() {
+        loom::model(|| {
+            let entries: Vec<_> = (0..4).map(|_| AtomicU32::new(0)).collect();
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            for value in [7, 8, 9] {
+                let entry = producer.reserve().unwrap();
+                entry.store(value, Ordering::Relaxed);
+                producer.commit(entry).unwrap();
+            }
+
+            let found: Vec<_> = producer
+                .entries()
+                .iter()
+                .map(|entry| entry.load(Ordering::Relaxed))
+                .filter(|value| *value != 0)
+                .collect();
+            assert_eq!(producer.entries().len(), 4);
+            assert_eq!(found, [7, 8, 9]);
+        });
+    }
+
     #[test]
     fn committing_entries_out_of_order_returns_error() {
         loom::model(|| {
@@ -332,6 +855,362 @@ mod test {
         });
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "impossible commit order")]
+    fn committing_entry_from_another_ring_panics_in_debug() {
+        loom::model(|| {
+            let entries = vec![0u32; 2];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let other_tail = AtomicU32::new(0);
+            let mask = 2 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+            let other = RingBufferProducer::new(&entries, &head, &other_tail, mask).unwrap();
+
+            let entry = other.reserve().unwrap();
+
+            // the index matches the tail, so without the assertion this would be accepted
+            let _ = producer.commit(entry);
+        });
+    }
+
+    #[test]
+    fn reserve_blocking_makes_progress_with_every_strategy() {
+        let strategies = [
+            BackoffStrategy::default(),
+            BackoffStrategy::spin_only(),
+            BackoffStrategy::sleep_only(Duration::from_micros(1)),
+        ];
+
+        for strategy in strategies {
+            loom::model(move || {
+                const ENTRIES: usize = 2;
+                let entries = Arc::new(vec![0u32; ENTRIES]);
+                let c_entries = entries.clone();
+                let mask = u32::try_from(ENTRIES).unwrap() - 1;
+
+                let head = Arc::new(AtomicU32::new(0));
+                let c_head = head.clone();
+
+                // start with a full ring, so the producer must wait for the consumer
+                let tail = Arc::new(AtomicU32::new(u32::try_from(ENTRIES).unwrap()));
+                let c_tail = tail.clone();
+
+                thread::spawn(move || {
+                    let consumer =
+                        RingBufferConsumer::new(&c_entries, &c_head, &c_tail, mask).unwrap();
+                    let entry = consumer.reserve().unwrap();
+                    consumer.commit(entry).unwrap();
+                });
+
+                let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+                let entry = producer.reserve_blocking(strategy);
+                producer.commit(entry).unwrap();
+
+                assert_eq!(tail.load(Ordering::Acquire), 3);
+            });
+        }
+    }
+
+    #[test]
+    fn reserve_fair_reserves_up_to_max_entries() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let batch = producer.reserve_fair(3).unwrap();
+            assert_eq!(batch.len(), 3);
+            producer.commit_batch(batch).unwrap();
+            assert_eq!(tail.load(Ordering::Acquire), 3);
+        });
+    }
+
+    #[test]
+    fn reserve_fair_reserves_what_is_available() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(3);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let batch = producer.reserve_fair(4).unwrap();
+            assert_eq!(batch.len(), 1);
+            assert!(producer.reserve_fair(4).is_none());
+        });
+    }
+
+    #[test]
+    fn reserve_fair_shares_entries_between_producers() {
+        let mut model = loom::model::Builder::new();
+        // limit search space or this will run for a long time
+        model.preemption_bound = Some(2);
+
+        model.check(|| {
+            const ENTRIES: usize = 8;
+            const PRODUCERS: u32 = 3;
+            const BATCH: u32 = 2;
+
+            // producers share a single `RingBufferProducer`, which must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![0u32; ENTRIES].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let producer = Arc::new(RingBufferProducer::new(entries, head, tail, mask).unwrap());
+
+            let handles: Vec<_> = (0..PRODUCERS)
+                .map(|_| {
+                    let producer = producer.clone();
+                    thread::spawn(move || {
+                        loop {
+                            if let Some(batch) = producer.reserve_fair(BATCH) {
+                                return batch;
+                            }
+
+                            yield_now();
+                        }
+                    })
+                })
+                .collect();
+
+            let mut batches: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+            batches.sort_unstable_by_key(|batch| batch.index);
+
+            // every batch must start where the previous one ended, with nothing lost or overlapping
+            for batch in batches {
+                assert_eq!(batch.index, tail.load(Ordering::Acquire));
+                assert_eq!(batch.len, BATCH);
+                producer.commit_batch(batch).unwrap();
+            }
+            assert_eq!(tail.load(Ordering::Acquire), PRODUCERS * BATCH);
+        });
+    }
+
+    #[test]
+    fn reserve_adaptive_reserves_batches_once_contended() {
+        loom::model(|| {
+            let entries = vec![0u32; 8];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 8 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask)
+                .unwrap()
+                .with_adaptive_reserve(1, 4);
+
+            let batch = producer.reserve_adaptive().unwrap();
+            assert_eq!(batch.len(), 1);
+            producer.commit_batch(batch).unwrap();
+
+            // pretend another thread won the last race
+            producer.contention.store(1, Ordering::Relaxed);
+            let batch = producer.reserve_adaptive().unwrap();
+            assert_eq!(batch.len(), 4);
+            producer.commit_batch(batch).unwrap();
+
+            let batch = producer.reserve_adaptive().unwrap();
+            assert_eq!(batch.len(), 1);
+            producer.commit_batch(batch).unwrap();
+            assert_eq!(tail.load(Ordering::Acquire), 6);
+        });
+    }
+
+    #[test]
+    fn reserve_adaptive_keeps_nothing_back_from_other_reservations() {
+        loom::model(|| {
+            let entries = vec![0u32; 8];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 8 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask)
+                .unwrap()
+                .with_adaptive_reserve(1, 4);
+
+            // pretend another thread won the last race, so a run is claimed
+            producer.contention.store(1, Ordering::Relaxed);
+            let batch = producer.reserve_adaptive().unwrap();
+            assert_eq!(batch.len(), 4);
+            assert_eq!(producer.pending_commits(), 4);
+            producer.commit_batch(batch).unwrap();
+
+            // every claimed entry was handed out, so plain reservations commit straight away
+            let entry = producer.reserve().unwrap();
+            assert_eq!(entry.index, 4);
+            producer.commit(entry).unwrap();
+            assert_eq!(producer.pending_commits(), 0);
+            assert_eq!(tail.load(Ordering::Acquire), 5);
+        });
+    }
+
+    #[test]
+    fn reserve_adaptive_never_loses_or_overlaps_between_producers() {
+        let mut builder = loom::model::Builder::new();
+        builder.preemption_bound = Some(2);
+
+        builder.check(|| {
+            const ENTRIES: usize = 8;
+            const PRODUCERS: u32 = 3;
+
+            // producers share a single `RingBufferProducer`, which must outlive the threads
+            let entries: &'static [u32] = Box::leak(vec![0u32; ENTRIES].into_boxed_slice());
+            let head: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let tail: &'static AtomicU32 = Box::leak(Box::new(AtomicU32::new(0)));
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+            let producer = Arc::new(
+                RingBufferProducer::new(entries, head, tail, mask)
+                    .unwrap()
+                    .with_adaptive_reserve(1, 2),
+            );
+
+            let handles: Vec<_> = (0..PRODUCERS)
+                .map(|_| {
+                    let producer = producer.clone();
+                    thread::spawn(move || {
+                        loop {
+                            if let Some(batch) = producer.reserve_adaptive() {
+                                return batch;
+                            }
+
+                            yield_now();
+                        }
+                    })
+                })
+                .collect();
+
+            let mut batches: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+            batches.sort_unstable_by_key(|batch| batch.index);
+
+            // every batch must start where the previous one ended, with nothing lost or
+            // overlapping, and nothing left reserved once they are all committed
+            for batch in batches {
+                assert_eq!(batch.index, tail.load(Ordering::Acquire));
+                producer.commit_batch(batch).unwrap();
+            }
+            assert_eq!(producer.pending_commits(), 0);
+        });
+    }
+
+    #[test]
+    fn commit_upto_commits_every_entry_in_one_store() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let _first = producer.reserve().unwrap();
+            let _second = producer.reserve().unwrap();
+            let _third = producer.reserve().unwrap();
+            let last = producer.reserve().unwrap();
+
+            assert!(producer.commit_upto(last).is_ok());
+            assert_eq!(tail.load(Ordering::Acquire), 4);
+        });
+    }
+
+    #[test]
+    fn commit_upto_rejects_entries_not_reserved_by_this_producer() {
+        loom::model(|| {
+            let entries = vec![0u32; 4];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 4 - 1;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+            let other = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let _first = producer.reserve().unwrap();
+            let second = producer.reserve().unwrap();
+
+            let (entry, error) = other.commit_upto(second).unwrap_err();
+            assert_eq!(entry.index, 1);
+            assert_eq!(error, RingBufferError::CommitOutOfOrder);
+            assert_eq!(tail.load(Ordering::Acquire), 0);
+        });
+    }
+
+    #[test]
+    fn single_entry_ring_holds_one_entry_at_a_time() {
+        loom::model(|| {
+            let entries = vec![0u32; 1];
+            let head = AtomicU32::new(0);
+            let tail = AtomicU32::new(0);
+            let mask = 0;
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+
+            let entry = producer.reserve().unwrap();
+            assert!(producer.reserve().is_none());
+            producer.commit(entry).unwrap();
+            assert!(producer.reserve().is_none());
+
+            head.fetch_add(1, Ordering::Release);
+            let entry = producer.reserve().unwrap();
+            producer.commit(entry).unwrap();
+            assert_eq!(tail.load(Ordering::Acquire), 2);
+        });
+    }
+
+    #[test]
+    fn observes_head_advanced_by_kernel_while_filling() {
+        let mut model = loom::model::Builder::new();
+        // limit search space or this will run for a long time
+        model.preemption_bound = Some(3);
+
+        model.check(|| {
+            const ENTRIES: usize = 2;
+            const PRODUCED: u32 = 4;
+            let entries: Arc<Vec<_>> = Arc::new((0..ENTRIES).map(|_| AtomicU32::new(0)).collect());
+            let k_entries = entries.clone();
+            let mask = u32::try_from(ENTRIES).unwrap() - 1;
+
+            let head = Arc::new(AtomicU32::new(0));
+            let k_head = head.clone();
+
+            let tail = Arc::new(AtomicU32::new(0));
+            let k_tail = tail.clone();
+
+            // behaves like the SQ polling thread: consumes whatever has been committed, without
+            // going through a `RingBufferConsumer`
+            let kernel = thread::spawn(move || {
+                let mut consumed = Vec::new();
+
+                while consumed.len() < PRODUCED as usize {
+                    let head = k_head.load(Ordering::Relaxed);
+                    if head == k_tail.load(Ordering::Acquire) {
+                        yield_now();
+                        continue;
+                    }
+
+                    let entry = &k_entries[(head & mask) as usize];
+                    consumed.push(entry.load(Ordering::Relaxed));
+                    k_head.store(head + 1, Ordering::Release);
+                }
+
+                consumed
+            });
+
+            let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+            for value in 1..=PRODUCED {
+                let entry = loop {
+                    if let Some(entry) = producer.reserve() {
+                        break entry;
+                    }
+
+                    yield_now();
+                };
+
+                entry.store(value, Ordering::Relaxed);
+                producer.commit(entry).unwrap();
+            }
+
+            // every submission arrives exactly once, in order
+            assert_eq!(kernel.join().unwrap(), (1..=PRODUCED).collect::<Vec<_>>());
+        });
+    }
+
     #[test]
     fn reserves_entry_when_some_are_available() {
         loom::model(|| {
@@ -366,9 +1245,13 @@ mod test {
 
 #[cfg(feature = "internal_benches")]
 mod benches {
+    use std::thread;
+    use std::time::Duration;
+
     use divan::{Bencher, counter::ItemsCount};
 
-    use super::{AtomicU32, Ordering, RingBufferProducer};
+    use super::{AtomicU32, BackoffStrategy, Ordering, RingBufferProducer};
+    use crate::RingBufferConsumer;
 
     const LENGTHS: &[usize] = &[64, 128, 1024, 2048];
 
@@ -380,13 +1263,68 @@ mod benches {
         let mask = u32::try_from(N).unwrap() - 1;
         let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
 
+        // every iteration fills the ring from empty, then releases every entry as the consumer
+        // would, so each iteration measures the same full cycle however many have run before
         bencher.counter(ItemsCount::new(N)).bench(|| {
             for _ in 0..N {
-                if let Some(item) = producer.reserve() {
-                    let _ = producer.commit(item);
-                }
+                let item = producer.reserve().unwrap();
+                producer.commit(item).unwrap();
             }
-            head.fetch_add(u32::try_from(N).unwrap(), Ordering::Release)
+            head.fetch_add(u32::try_from(N).unwrap(), Ordering::Release);
         });
     }
+
+    const STRATEGIES: &[&str] = &["default", "spin_only", "sleep_only"];
+
+    #[divan::bench(args = STRATEGIES)]
+    fn reserve_blocking_contended(bencher: Bencher, strategy: &str) {
+        const ENTRIES: usize = 64;
+        const PRODUCERS: usize = 4;
+        const PER_PRODUCER: usize = 256;
+
+        let strategy = match strategy {
+            "spin_only" => BackoffStrategy::spin_only(),
+            "sleep_only" => BackoffStrategy::sleep_only(Duration::from_micros(50)),
+            _ => BackoffStrategy::default(),
+        };
+        let entries = vec![0u32; ENTRIES];
+        let mask = u32::try_from(ENTRIES).unwrap() - 1;
+
+        bencher
+            .counter(ItemsCount::new(PRODUCERS * PER_PRODUCER))
+            .bench(|| {
+                let head = AtomicU32::new(0);
+                let tail = AtomicU32::new(0);
+                let producer = RingBufferProducer::new(&entries, &head, &tail, mask).unwrap();
+                let consumer = RingBufferConsumer::new(&entries, &head, &tail, mask).unwrap();
+
+                thread::scope(|s| {
+                    for _ in 0..PRODUCERS {
+                        s.spawn(|| {
+                            for _ in 0..PER_PRODUCER {
+                                let entry = producer.reserve_blocking(strategy);
+                                // entries must be committed in the order they were reserved
+                                while tail.load(Ordering::Acquire) != entry.index {
+                                    std::hint::spin_loop();
+                                }
+                                let _ = producer.commit(entry);
+                            }
+                        });
+                    }
+
+                    s.spawn(|| {
+                        for _ in 0..PRODUCERS * PER_PRODUCER {
+                            loop {
+                                if let Some(entry) = consumer.reserve() {
+                                    let _ = consumer.commit(entry);
+                                    break;
+                                }
+
+                                std::hint::spin_loop();
+                            }
+                        }
+                    });
+                });
+            });
+    }
 }
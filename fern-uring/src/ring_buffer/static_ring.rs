@@ -0,0 +1,174 @@
+//! A ring buffer which stores a fixed number of entries inline, checked at compile time.
+//!
+//! Like an [`OwnedRingBuffer`](super::OwnedRingBuffer), a `StaticRingBuffer` owns its entries,
+//! head, and tail, but its length is a const generic. Lengths which are not a power of two fail to
+//! compile, rather than returning [`RingBufferError::LengthNotPowerOfTwo`] at runtime.
+//!
+//! [`RingBufferError::LengthNotPowerOfTwo`]: super::RingBufferError::LengthNotPowerOfTwo
+
+use std::sync::atomic::Ordering;
+
+use super::{RingBufferConsumer, RingBufferProducer};
+use crate::sync::atomic::AtomicU32;
+
+/// A ring buffer which stores `N` entries inline, along with its head and tail.
+///
+/// `N` must be a power of two which fits in a `u32`, otherwise constructing the ring buffer fails
+/// to compile:
+///
+/// ```compile_fail
+/// # use fern_uring::StaticRingBuffer;
+/// let ring = StaticRingBuffer::<u32, 3>::new();
+/// ```
+///
+/// Access is provided through a [`RingBufferProducer`] and [`RingBufferConsumer`] pair, given to
+/// a closure by [`Self::with_split`].
+#[derive(Debug)]
+pub struct StaticRingBuffer<T, const N: usize> {
+    entries: [T; N],
+    head: AtomicU32,
+    tail: AtomicU32,
+}
+
+impl<T, const N: usize> StaticRingBuffer<T, N> {
+    // only used once `from_array` has checked `N` is a power of two which fits in a `u32`
+    #[allow(clippy::cast_possible_truncation)]
+    const MASK: u32 = N.wrapping_sub(1) as u32;
+
+    /// Creates a new, empty `StaticRingBuffer` holding `entries`.
+    #[must_use]
+    pub fn from_array(entries: [T; N]) -> Self {
+        const {
+            assert!(
+                N.is_power_of_two(),
+                "StaticRingBuffer length must be a power of two"
+            );
+            assert!(
+                N - 1 <= u32::MAX as usize,
+                "StaticRingBuffer length must fit in a u32"
+            );
+        };
+
+        Self {
+            entries,
+            head: AtomicU32::new(0),
+            tail: AtomicU32::new(0),
+        }
+    }
+
+    /// Get the size of the ring buffer.
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        N
+    }
+
+    /// Get the capacity of the ring buffer. This is an alias of [`Self::size`].
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.size()
+    }
+
+    /// Get the number of occupied entries in the ring buffer.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.tail
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.head.load(Ordering::Acquire)) as usize
+    }
+
+    /// Determines if the ring buffer has no occupied entries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Splits the ring buffer into a producer and consumer, passing both to `f`.
+    ///
+    /// The producer and consumer only live for the duration of `f`. Entries which were reserved,
+    /// but not committed, when `f` returns are released, and may be reserved again by the next
+    /// split. Taking `&mut self` ensures only one producer and consumer pair exists at a time.
+    pub fn with_split<F, R>(&mut self, f: F) -> R
+    where
+        F: FnOnce(&RingBufferProducer<'_, T>, &RingBufferConsumer<'_, T>) -> R,
+    {
+        // `N` was checked at compile time, so neither side can fail validation
+        let split = RingBufferProducer::new(&self.entries, &self.head, &self.tail, Self::MASK)
+            .and_then(|producer| {
+                RingBufferConsumer::new(&self.entries, &self.head, &self.tail, Self::MASK)
+                    .map(|consumer| (producer, consumer))
+            });
+
+        match split {
+            Ok((producer, consumer)) => f(&producer, &consumer),
+            Err(e) => unreachable!("static ring buffer is always valid: {e}"),
+        }
+    }
+}
+
+impl<T: Default, const N: usize> StaticRingBuffer<T, N> {
+    /// Creates a new, empty `StaticRingBuffer`, with every entry set to its default value.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::from_array(std::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<T: Default, const N: usize> Default for StaticRingBuffer<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::StaticRingBuffer;
+    use crate::sync::atomic::{AtomicU32, Ordering};
+
+    #[test]
+    fn holds_power_of_two_entries() {
+        loom::model(|| {
+            let mut ring = StaticRingBuffer::<AtomicU32, 8>::new();
+            assert_eq!(ring.size(), 8);
+            assert_eq!(ring.capacity(), 8);
+            assert!(ring.is_empty());
+
+            ring.with_split(|producer, consumer| {
+                for value in 1..=8 {
+                    let entry = producer.reserve().unwrap();
+                    entry.store(value, Ordering::Relaxed);
+                    producer.commit(entry).unwrap();
+                }
+                assert!(producer.reserve().is_none());
+
+                for value in 1..=8 {
+                    let entry = consumer.reserve().unwrap();
+                    assert_eq!(entry.load(Ordering::Relaxed), value);
+                    consumer.commit(entry).unwrap();
+                }
+                assert!(consumer.reserve().is_none());
+            });
+
+            assert!(ring.is_empty());
+        });
+    }
+
+    #[test]
+    fn from_array_keeps_entries() {
+        loom::model(|| {
+            let mut ring = StaticRingBuffer::from_array([1u32, 2]);
+
+            ring.with_split(|producer, consumer| {
+                for _ in 0..2 {
+                    let entry = producer.reserve().unwrap();
+                    producer.commit(entry).unwrap();
+                }
+
+                for value in 1..=2 {
+                    let entry = consumer.reserve().unwrap();
+                    assert_eq!(*entry, value);
+                    consumer.commit(entry).unwrap();
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,132 @@
+//! Heap storage for ring buffer entries, with control over alignment.
+
+use std::alloc::{Layout, alloc, dealloc, handle_alloc_error};
+use std::fmt::Debug;
+use std::mem::ManuallyDrop;
+use std::ops::Deref;
+use std::ptr::{self, NonNull};
+
+use super::RingBufferError;
+
+/// A heap allocated slice of entries, aligned to at least the alignment of `T`.
+pub(crate) struct Storage<T> {
+    ptr: NonNull<T>,
+    len: usize,
+    layout: Layout,
+}
+
+// SAFETY: `Storage` uniquely owns its entries, like `Box<[T]>`.
+unsafe impl<T: Send> Send for Storage<T> {}
+// SAFETY: `Storage` only gives out shared access to its entries through `&self`.
+unsafe impl<T: Sync> Sync for Storage<T> {}
+
+impl<T> Storage<T> {
+    /// Allocate `len` entries aligned to `align`, initializing each with `init`.
+    ///
+    /// If `init` panics, the allocation and any initialized entries are leaked.
+    pub(crate) fn new_aligned(
+        len: usize,
+        align: usize,
+        mut init: impl FnMut() -> T,
+    ) -> Result<Self, RingBufferError> {
+        if !align.is_power_of_two() {
+            return Err(RingBufferError::InvalidAlignment);
+        }
+
+        let layout = Layout::array::<T>(len)
+            .and_then(|layout| layout.align_to(align))
+            .map_err(|_| RingBufferError::EntriesSliceTooLong)?;
+
+        let raw = if layout.size() == 0 {
+            // zero sized allocations only need a well aligned, non-null pointer
+            ptr::without_provenance_mut(layout.align())
+        } else {
+            // SAFETY: `layout` has a non-zero size.
+            unsafe { alloc(layout) }.cast::<T>()
+        };
+        let Some(ptr) = NonNull::new(raw) else {
+            handle_alloc_error(layout);
+        };
+
+        for i in 0..len {
+            // SAFETY: `ptr` was allocated with room for `len` entries of `T`.
+            unsafe { ptr.add(i).write(init()) };
+        }
+
+        Ok(Self { ptr, len, layout })
+    }
+
+    /// Get the alignment the entries were allocated with.
+    pub(crate) fn align(&self) -> usize {
+        self.layout.align()
+    }
+
+    /// Take ownership of the entries of `entries`, reusing its allocation.
+    pub(crate) fn from_boxed(entries: Box<[T]>) -> Self {
+        let len = entries.len();
+        // `Box<[T]>` allocates with exactly this layout, which cannot overflow for a live box
+        let layout = Layout::for_value::<[T]>(&entries);
+        // SAFETY: `Box::into_raw` never returns a null pointer.
+        let ptr = unsafe { NonNull::new_unchecked(Box::into_raw(entries).cast::<T>()) };
+
+        Self { ptr, len, layout }
+    }
+
+    /// Give up ownership of the entries as a `Box<[T]>`.
+    ///
+    /// The allocation is handed over as-is when it has the layout `Box<[T]>` expects. Entries
+    /// allocated with a larger alignment than `T` are moved into a new allocation instead, as a
+    /// `Box<[T]>` would deallocate them with the wrong layout.
+    pub(crate) fn into_boxed(self) -> Box<[T]> {
+        let this = ManuallyDrop::new(self);
+        let entries = ptr::slice_from_raw_parts_mut(this.ptr.as_ptr(), this.len);
+
+        if Layout::array::<T>(this.len).is_ok_and(|layout| layout == this.layout) {
+            // SAFETY: `ptr` points to `len` initialized entries, allocated with the layout of
+            // `[T]`, and ownership is transferred as `this` is never dropped.
+            return unsafe { Box::from_raw(entries) };
+        }
+
+        let mut moved = Vec::with_capacity(this.len);
+        // SAFETY: `ptr` points to `len` initialized entries, which are moved into `moved` exactly
+        // once, as `this` is never dropped.
+        unsafe {
+            ptr::copy_nonoverlapping(this.ptr.as_ptr(), moved.as_mut_ptr(), this.len);
+            moved.set_len(this.len);
+        }
+
+        if this.layout.size() != 0 {
+            // SAFETY: `ptr` was allocated with `layout`, and its entries were moved out.
+            unsafe { dealloc(this.ptr.as_ptr().cast(), this.layout) };
+        }
+
+        moved.into_boxed_slice()
+    }
+}
+
+impl<T> Deref for Storage<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: `ptr` points to `len` initialized entries, which live as long as `self`.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T> Drop for Storage<T> {
+    fn drop(&mut self) {
+        // SAFETY: `ptr` points to `len` initialized entries, which are never used again.
+        unsafe { ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len)) };
+
+        if self.layout.size() != 0 {
+            // SAFETY: `ptr` was allocated with `layout`.
+            unsafe { dealloc(self.ptr.as_ptr().cast(), self.layout) };
+        }
+    }
+}
+
+impl<T: Debug> Debug for Storage<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -5,3 +5,22 @@ pub use loom::sync::*;
 
 #[cfg(not(test))]
 pub use std::sync::*;
+
+#[cfg(test)]
+pub use loom::hint;
+
+#[cfg(not(test))]
+pub use std::hint;
+
+#[cfg(test)]
+pub mod thread {
+    pub use loom::thread::yield_now;
+
+    /// Loom can't model the passage of time, so a sleep is modeled as a yield.
+    pub fn sleep(_duration: std::time::Duration) {
+        yield_now();
+    }
+}
+
+#[cfg(not(test))]
+pub use std::thread;
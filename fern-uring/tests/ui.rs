@@ -0,0 +1,10 @@
+//! Checks which uses of the public API compile, and that misuse is rejected at compile time.
+
+#[test]
+fn ui() {
+    let cases = trybuild::TestCases::new();
+    // having passing cases makes trybuild build, rather than only check, every case, which is
+    // needed to reach errors from const assertions on generic parameters
+    cases.pass("tests/ui/pass/*.rs");
+    cases.compile_fail("tests/ui/fail/*.rs");
+}
@@ -0,0 +1,10 @@
+use std::cell::UnsafeCell;
+
+use fern_uring::ReservedEntry;
+
+fn assert_send<T: Send>() {}
+
+fn main() {
+    // writes through `as_mut_ptr` are unsynchronized, so the reservation can't cross threads
+    assert_send::<ReservedEntry<'static, UnsafeCell<u32>>>();
+}
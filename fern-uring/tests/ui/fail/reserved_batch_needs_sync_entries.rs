@@ -0,0 +1,12 @@
+use std::cell::Cell;
+
+use fern_uring::ReservedBatch;
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+fn main() {
+    // a reservation shares its entries, so it can only cross threads if they are `Sync`
+    assert_send::<ReservedBatch<'static, Cell<u32>>>();
+    assert_sync::<ReservedBatch<'static, Cell<u32>>>();
+}
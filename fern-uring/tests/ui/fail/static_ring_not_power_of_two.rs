@@ -0,0 +1,5 @@
+use fern_uring::StaticRingBuffer;
+
+fn main() {
+    let _ring = StaticRingBuffer::<u32, 3>::new();
+}
@@ -0,0 +1,6 @@
+use fern_uring::StaticRingBuffer;
+
+fn main() {
+    let ring = StaticRingBuffer::<u32, 4>::new();
+    assert_eq!(ring.size(), 4);
+}